@@ -0,0 +1,208 @@
+use serde_norway::{Mapping, Value};
+use std::path::Path;
+
+use crate::ToCheck;
+
+/// Loads the items from a compose file
+///
+/// The project name is taken from the `name` key
+/// or from the directory containing the file.
+pub fn load(path: &Path, service: Option<&str>) -> Result<Vec<ToCheck>, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|err| format!("'{}': {err}", path.display()))?;
+    let directory = path
+        .canonicalize()
+        .ok()
+        .and_then(|e| Some(e.parent()?.file_name()?.to_string_lossy().to_lowercase()))
+        .unwrap_or_default()
+        .chars()
+        // same normalization as docker compose does
+        .filter(|e| e.is_ascii_alphanumeric() || "_-".contains(*e))
+        .collect::<String>();
+    parse(&content, &directory, service).map_err(|err| format!("'{}': {err}", path.display()))
+}
+
+/// Parses the items from the content of a compose file
+///
+/// When `service` is set, only the services it depends on are used
+/// and each of them has to yield an item.
+/// All the services which yield items are used otherwise.
+///
+/// ```
+/// use wait_for_them::compose;
+///
+/// let targets = compose::parse(
+///     "
+/// services:
+///   db:
+///     image: postgres
+///     expose: [5432]
+///   web:
+///     image: nginx
+///     ports: ['8080:80']
+///     depends_on: [db]
+/// ",
+///     "example",
+///     Some("web"),
+/// )
+/// .unwrap();
+/// assert_eq!(targets.len(), 1);
+/// assert_eq!(targets[0].to_string(), "db:5432");
+/// ```
+pub fn parse(content: &str, project: &str, service: Option<&str>) -> Result<Vec<ToCheck>, String> {
+    let compose: Value = serde_norway::from_str(content).map_err(|err| err.to_string())?;
+    let services = compose
+        .get("services")
+        .and_then(Value::as_mapping)
+        .ok_or_else(|| "services are missing".to_string())?;
+    let project = compose
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or(project);
+
+    let names: Vec<String> = match service {
+        Some(service) => {
+            let definition = services
+                .get(service)
+                .ok_or_else(|| format!("service '{service}' is not defined"))?;
+            depends_on(service, definition)?
+        }
+        None => services
+            .keys()
+            .map(|name| text(name, "service name"))
+            .collect::<Result<_, _>>()?,
+    };
+
+    let mut targets = vec![];
+    for name in names {
+        let definition = services
+            .get(name.as_str())
+            .ok_or_else(|| format!("service '{name}' is not defined"))?;
+        let service_targets = service_targets(project, &name, definition)?;
+        if service_targets.is_empty() && service.is_some() {
+            return Err(format!(
+                "service '{name}' has neither ports nor healthcheck"
+            ));
+        }
+        targets.extend(service_targets);
+    }
+    Ok(targets)
+}
+
+fn text(value: &Value, what: &str) -> Result<String, String> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Number(value) => Ok(value.to_string()),
+        _ => Err(format!("{what} is not a string")),
+    }
+}
+
+/// Names of the services from the short (list) or the long (mapping) syntax of `depends_on`
+fn depends_on(service: &str, definition: &Value) -> Result<Vec<String>, String> {
+    match definition.get("depends_on") {
+        None => Ok(vec![]),
+        Some(Value::Sequence(names)) => names
+            .iter()
+            .map(|name| text(name, &format!("service '{service}', depends_on item")))
+            .collect(),
+        Some(Value::Mapping(names)) => names
+            .keys()
+            .map(|name| text(name, &format!("service '{service}', depends_on key")))
+            .collect(),
+        Some(_) => Err(format!(
+            "service '{service}', depends_on is not a list nor a mapping"
+        )),
+    }
+}
+
+fn service_targets(project: &str, name: &str, definition: &Value) -> Result<Vec<ToCheck>, String> {
+    let mut ports = vec![];
+    let entries = ["expose", "ports"]
+        .into_iter()
+        .filter_map(|key| Some((key, definition.get(key)?)));
+    for (key, entry) in entries {
+        let entry = entry
+            .as_sequence()
+            .ok_or_else(|| format!("service '{name}', {key} is not a list"))?;
+        for port in entry {
+            let parsed = match port {
+                Value::Mapping(port) => long_port(port),
+                port => short_port(&text(port, "port")?),
+            }
+            .ok_or_else(|| format!("service '{name}', invalid {key} item {port:?}"))?;
+            for port in parsed {
+                if !ports.contains(&port) {
+                    ports.push(port);
+                }
+            }
+        }
+    }
+
+    #[cfg_attr(not(feature = "docker"), allow(unused_mut))]
+    let mut targets: Vec<ToCheck> = ports
+        .into_iter()
+        .map(|port| ToCheck::HostnameAndPort(name.to_string(), port))
+        .collect();
+
+    #[cfg(feature = "docker")]
+    if has_healthcheck(definition) {
+        let container = definition
+            .get("container_name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            // default name of docker compose v2
+            .unwrap_or_else(|| format!("{project}-{name}-1"));
+        targets.push(ToCheck::Docker(crate::DockerCheck { container }));
+    }
+    #[cfg(not(feature = "docker"))]
+    let _ = project;
+
+    Ok(targets)
+}
+
+#[cfg(feature = "docker")]
+fn has_healthcheck(definition: &Value) -> bool {
+    let Some(healthcheck) = definition.get("healthcheck") else {
+        return false;
+    };
+    if healthcheck.get("disable").and_then(Value::as_bool) == Some(true) {
+        return false;
+    }
+    match healthcheck.get("test") {
+        Some(Value::Sequence(test)) => test.first().and_then(Value::as_str) != Some("NONE"),
+        Some(_) => true,
+        // test may be inherited from the image
+        None => true,
+    }
+}
+
+/// Container ports of the long syntax, e.g. `{target: 80, published: 8080}`
+fn long_port(port: &Mapping) -> Option<Vec<u16>> {
+    let protocol = port
+        .get("protocol")
+        .and_then(Value::as_str)
+        .unwrap_or("tcp");
+    if protocol != "tcp" {
+        return Some(vec![]);
+    }
+    let target = text(port.get("target")?, "target").ok()?;
+    port_range(&target)
+}
+
+/// Container ports of the short syntax, e.g. `5432`, `8080:80`, `127.0.0.1:8080:80/tcp`
+fn short_port(port: &str) -> Option<Vec<u16>> {
+    let (port, protocol) = port.split_once('/').unwrap_or((port, "tcp"));
+    if protocol != "tcp" {
+        return Some(vec![]);
+    }
+    // container port is always the last one
+    port_range(port.rsplit(':').next()?)
+}
+
+/// Parses a port or a range of ports such as `3000-3005`
+fn port_range(range: &str) -> Option<Vec<u16>> {
+    let (start, end) = range.split_once('-').unwrap_or((range, range));
+    let start: u16 = start.trim().parse().ok()?;
+    let end: u16 = end.trim().parse().ok()?;
+    (start <= end).then(|| (start..=end).collect())
+}
@@ -0,0 +1,361 @@
+use serde::{
+    de::{self, Deserializer, MapAccess, SeqAccess, Visitor},
+    Deserialize,
+};
+use std::{fmt, path::Path, str::FromStr, time::Duration};
+
+use crate::{
+    parse_duration, parse_name, Group, Output, Quorum, RetryPolicy, Target, TargetOptions, ToCheck,
+};
+
+/// Keys of an item written as a table
+const TARGET_FIELDS: &[&str] = &[
+    "item",
+    "timeout",
+    "required",
+    "stable",
+    "stable_for",
+    "retry",
+    "attempt_timeout",
+    "name",
+    "after",
+];
+
+/// Keys of an item written as a table which hold durations
+const DURATION_FIELDS: &[&str] = &["timeout", "stable_for", "attempt_timeout"];
+
+/// Plain numbers of milliseconds below this are likely meant as seconds
+const SUSPICIOUS_MILLIS: u128 = 1000;
+
+/// Format of a configuration file
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Guesses the format from the extension of the file
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Items and options loaded from a configuration file
+///
+/// The options have the same meaning as the options of the binary,
+/// plain numbers of durations below 1000 milliseconds are rejected as likely meant as seconds.
+/// Items are either strings in the same format as on the command line
+/// or tables with the `item` key and the options of [`Target`].
+///
+/// ```
+/// use std::time::Duration;
+/// use wait_for_them::{Config, ConfigFormat, Quorum};
+///
+/// let config = Config::parse(r#"
+/// timeout = "30s"
+/// retry = "exponential:100ms:5s"
+/// targets = [
+///     "localhost:5432@name=db",
+///     { item = "http://localhost:8080/health", after = "db", stable = 3 },
+/// ]
+/// command = ["./start.sh"]
+///
+/// [[groups]]
+/// name = "cache"
+/// quorum = "any"
+/// targets = ["redis-a:6379", "redis-b:6379"]
+/// "#, ConfigFormat::Toml).unwrap();
+/// assert_eq!(config.timeout, Some(Duration::from_secs(30)));
+/// assert_eq!(config.targets[1].options.after, vec!["db"]);
+/// assert_eq!(config.groups[0].quorum, Quorum::Any);
+/// ```
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(deserialize_with = "duration")]
+    pub timeout: Option<Duration>,
+    #[serde(deserialize_with = "positive")]
+    pub stable: Option<u32>,
+    #[serde(deserialize_with = "duration")]
+    pub stable_for: Option<Duration>,
+    #[serde(deserialize_with = "parsed")]
+    pub retry: Option<RetryPolicy>,
+    #[serde(deserialize_with = "attempt_timeout")]
+    pub attempt_timeout: Option<Duration>,
+    /// Quorum of `targets`
+    pub quorum: Option<Quorum>,
+    pub targets: Vec<Target>,
+    pub groups: Vec<Group>,
+    /// Command executed once the items are ready
+    pub command: Option<Vec<String>>,
+    pub silent: Option<bool>,
+    /// `text`, `json` or `ndjson`
+    #[serde(deserialize_with = "parsed")]
+    pub output: Option<Output>,
+}
+
+impl Config {
+    /// Loads the configuration from a `.toml`, `.yaml`, `.yml` or `.json` file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let format = ConfigFormat::from_path(path).ok_or_else(|| {
+            format!(
+                "'{}' is not a .toml, .yaml, .yml or .json file",
+                path.display()
+            )
+        })?;
+        let content =
+            std::fs::read_to_string(path).map_err(|err| format!("'{}': {err}", path.display()))?;
+        Self::parse(&content, format).map_err(|err| format!("'{}': {err}", path.display()))
+    }
+
+    /// Parses the configuration, errors contain the line of the invalid value
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self, String> {
+        match format {
+            ConfigFormat::Toml => toml::from_str(content).map_err(|err| err.to_string()),
+            ConfigFormat::Yaml => serde_norway::from_str(content).map_err(|err| err.to_string()),
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|err| err.to_string()),
+        }
+    }
+
+    /// Options applied to the items which don't set them
+    pub fn defaults(&self) -> TargetOptions {
+        TargetOptions {
+            stable: self.stable,
+            stable_for: self.stable_for,
+            retry: self.retry,
+            attempt_timeout: self.attempt_timeout,
+            ..TargetOptions::default()
+        }
+    }
+}
+
+/// String, number or boolean converted to the form used on the command line
+struct Scalar(String);
+
+impl<'de> Deserialize<'de> for Scalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ScalarVisitor;
+
+        impl Visitor<'_> for ScalarVisitor {
+            type Value = Scalar;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a string, a number or a boolean")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Scalar, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Scalar, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Scalar, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Scalar, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<Scalar, E> {
+                Ok(Scalar(value.to_string()))
+            }
+        }
+
+        deserializer.deserialize_any(ScalarVisitor)
+    }
+}
+
+/// Single name or a list of names
+struct Names(Vec<String>);
+
+impl<'de> Deserialize<'de> for Names {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NamesVisitor;
+
+        impl<'de> Visitor<'de> for NamesVisitor {
+            type Value = Names;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a name or a list of names")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Names, E> {
+                Ok(Names(vec![value.to_string()]))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Names, A::Error> {
+                let mut names = vec![];
+                while let Some(name) = seq.next_element()? {
+                    names.push(name);
+                }
+                Ok(Names(names))
+            }
+        }
+
+        deserializer.deserialize_any(NamesVisitor)
+    }
+}
+
+fn parsed<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let Scalar(value) = Scalar::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(de::Error::custom)
+}
+
+/// Parses a duration and rejects the small plain numbers which the command line warns about
+fn parse_config_duration(value: &str) -> Result<Duration, String> {
+    let duration = parse_duration(value)?;
+    let millis = duration.as_millis();
+    if value.bytes().all(|e| e.is_ascii_digit()) && millis > 0 && millis < SUSPICIOUS_MILLIS {
+        return Err(format!(
+            "'{value}' would be in milliseconds, use '{value}s' for seconds or '{value}ms'"
+        ));
+    }
+    Ok(duration)
+}
+
+fn duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let Scalar(value) = Scalar::deserialize(deserializer)?;
+    parse_config_duration(&value)
+        .map(Some)
+        .map_err(de::Error::custom)
+}
+
+fn attempt_timeout<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    match duration(deserializer)? {
+        Some(attempt_timeout) if attempt_timeout.is_zero() => {
+            Err(de::Error::custom("attempt_timeout has to be positive"))
+        }
+        attempt_timeout => Ok(attempt_timeout),
+    }
+}
+
+fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    let Scalar(value) = Scalar::deserialize(deserializer)?;
+    match value.parse() {
+        Ok(0) | Err(_) => Err(de::Error::custom(format!(
+            "'{value}' is not a positive number"
+        ))),
+        Ok(value) => Ok(Some(value)),
+    }
+}
+
+impl<'de> Deserialize<'de> for Quorum {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Scalar(value) = Scalar::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Target {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TargetVisitor;
+
+        impl<'de> Visitor<'de> for TargetVisitor {
+            type Value = Target;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an item or a table with the item and its options")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Target, E> {
+                let target: Target = value.parse().map_err(E::custom)?;
+                // the durations of the options suffix are checked as the ones of the tables
+                let suffix = value
+                    .rsplit_once('@')
+                    .filter(|_| target.options != TargetOptions::default());
+                if let Some((_, suffix)) = suffix {
+                    let pairs = suffix.split(',').filter_map(|e| e.split_once('='));
+                    for (key, value) in pairs.filter(|(key, _)| DURATION_FIELDS.contains(key)) {
+                        parse_config_duration(value)
+                            .map_err(|err| E::custom(format!("{key} {err}")))?;
+                    }
+                }
+                Ok(target)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Target, A::Error> {
+                let mut item: Option<ToCheck> = None;
+                let mut options = TargetOptions::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "item" => {
+                            let value: String = map.next_value()?;
+                            item = Some(value.parse().map_err(de::Error::custom)?);
+                        }
+                        "after" => {
+                            let Names(names) = map.next_value()?;
+                            for name in names {
+                                options
+                                    .after
+                                    .push(parse_name(&name).map_err(de::Error::custom)?);
+                            }
+                        }
+                        key if TARGET_FIELDS.contains(&key) => {
+                            let Scalar(value) = map.next_value()?;
+                            if DURATION_FIELDS.contains(&key) {
+                                parse_config_duration(&value)
+                                    .map_err(|err| de::Error::custom(format!("{key} {err}")))?;
+                            }
+                            options.set(key, &value).map_err(de::Error::custom)?;
+                        }
+                        key => return Err(de::Error::unknown_field(key, TARGET_FIELDS)),
+                    }
+                }
+                Ok(Target {
+                    to_check: item.ok_or_else(|| de::Error::missing_field("item"))?,
+                    options,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(TargetVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GroupTable {
+    name: Option<String>,
+    #[serde(default)]
+    quorum: Quorum,
+    targets: Vec<Target>,
+}
+
+impl<'de> Deserialize<'de> for Group {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let GroupTable {
+            name,
+            quorum,
+            targets,
+        } = GroupTable::deserialize(deserializer)?;
+        if let Some(name) = &name {
+            parse_name(name).map_err(de::Error::custom)?;
+        }
+        if quorum.needed(targets.len()) > targets.len() {
+            return Err(de::Error::custom(format!(
+                "group has less than {quorum} items"
+            )));
+        }
+        Ok(Self {
+            name,
+            quorum,
+            targets,
+        })
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use tokio::sync::watch;
+
+use crate::{Group, Target};
+
+/// Readiness of an item or a group, `None` while it is still pending
+pub(crate) type State = Option<bool>;
+
+/// Item or group which has to be ready before an item is checked
+pub(crate) struct Dependency {
+    pub name: String,
+    pub state: watch::Receiver<State>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    /// index of the item across all the groups
+    Target(usize),
+    Group(usize),
+}
+
+fn targets(groups: &[Group]) -> Vec<&Target> {
+    groups.iter().flat_map(|group| &group.targets).collect()
+}
+
+/// Maps the names to the items and the groups
+fn nodes(groups: &[Group]) -> Result<HashMap<&str, Node>, String> {
+    let named_targets = targets(groups)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, target)| Some((target.options.name.as_deref()?, Node::Target(idx))));
+    let named_groups = groups
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, group)| Some((group.name.as_deref()?, Node::Group(idx))));
+
+    let mut nodes = HashMap::new();
+    for (name, node) in named_targets.chain(named_groups) {
+        if nodes.insert(name, node).is_some() {
+            return Err(format!("name '{name}' is used multiple times"));
+        }
+    }
+    Ok(nodes)
+}
+
+/// Verifies that all the dependencies exist and that there are no cycles
+///
+/// A group depends on its items, so an item can't depend on its own group.
+///
+/// ```
+/// use wait_for_them::{check_dependencies, Group};
+///
+/// let group = Group {
+///     targets: vec![
+///         "localhost:5432@name=db".parse().unwrap(),
+///         "http://localhost:8080/health@after=db".parse().unwrap(),
+///     ],
+///     ..Group::default()
+/// };
+/// assert!(check_dependencies(&[group]).is_ok());
+/// ```
+pub fn check_dependencies(groups: &[Group]) -> Result<(), String> {
+    let nodes = nodes(groups)?;
+    let targets = targets(groups);
+
+    let mut offsets = vec![];
+    let mut offset = 0;
+    for group in groups {
+        offsets.push(offset);
+        offset += group.targets.len();
+    }
+
+    let edges = |node: Node| -> Result<Vec<Node>, String> {
+        match node {
+            Node::Target(idx) => targets[idx]
+                .options
+                .after
+                .iter()
+                .map(|name| {
+                    nodes
+                        .get(name.as_str())
+                        .copied()
+                        .ok_or_else(|| format!("'{}' depends on unknown '{name}'", targets[idx]))
+                })
+                .collect(),
+            Node::Group(idx) => Ok((offsets[idx]..offsets[idx] + groups[idx].targets.len())
+                .map(Node::Target)
+                .collect()),
+        }
+    };
+    let label = |node: Node| match node {
+        Node::Target(idx) => targets[idx]
+            .options
+            .name
+            .clone()
+            .unwrap_or_else(|| targets[idx].to_check.to_string()),
+        Node::Group(idx) => groups[idx].name.clone().unwrap_or_default(),
+    };
+
+    // depth first search which keeps the current path to report the cycle
+    let mut done = vec![false; targets.len()];
+    for start in 0..targets.len() {
+        if done[start] {
+            continue;
+        }
+        let mut path = vec![Node::Target(start)];
+        let mut pending = vec![edges(Node::Target(start))?];
+        while let Some(next) = pending.last_mut().map(Vec::pop) {
+            let Some(next) = next else {
+                if let Some(Node::Target(idx)) = path.pop() {
+                    done[idx] = true;
+                }
+                pending.pop();
+                continue;
+            };
+            if let Some(position) = path.iter().position(|e| *e == next) {
+                let cycle: Vec<String> = path[position..]
+                    .iter()
+                    .chain([&next])
+                    .map(|e| label(*e))
+                    .collect();
+                return Err(format!("dependency cycle {}", cycle.join(" -> ")));
+            }
+            if matches!(next, Node::Target(idx) if done[idx]) {
+                continue;
+            }
+            path.push(next);
+            pending.push(edges(next)?);
+        }
+    }
+    Ok(())
+}
+
+/// Channels announcing the readiness of the items and groups which others depend on
+pub(crate) struct Signals {
+    /// for each item across all the groups
+    pub targets: Vec<Option<watch::Sender<State>>>,
+    pub groups: Vec<Option<watch::Sender<State>>>,
+    /// dependencies of each item across all the groups
+    pub after: Vec<Vec<Dependency>>,
+}
+
+impl Signals {
+    /// Expects that the groups passed [`check_dependencies`]
+    pub fn new(groups: &[Group]) -> Self {
+        let targets = targets(groups);
+        let nodes = nodes(groups).unwrap_or_default();
+
+        let mut signals = Self {
+            targets: targets.iter().map(|_| None).collect(),
+            groups: groups.iter().map(|_| None).collect(),
+            after: vec![],
+        };
+        let mut receivers = HashMap::new();
+        for (name, node) in &nodes {
+            let (sender, receiver) = watch::channel(None);
+            match node {
+                Node::Target(idx) => signals.targets[*idx] = Some(sender),
+                Node::Group(idx) => signals.groups[*idx] = Some(sender),
+            }
+            receivers.insert(*name, receiver);
+        }
+        signals.after = targets
+            .iter()
+            .map(|target| {
+                target
+                    .options
+                    .after
+                    .iter()
+                    .filter_map(|name| {
+                        Some(Dependency {
+                            name: name.clone(),
+                            state: receivers.get(name.as_str())?.clone(),
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        signals
+    }
+}
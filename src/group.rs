@@ -0,0 +1,159 @@
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::{fmt, future::Future, str::FromStr, time::Instant};
+
+use crate::{scanner::WaitFuture, Target, TargetResult};
+
+/// How many items of a group have to be ready
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Quorum {
+    #[default]
+    All,
+    Any,
+    AtLeast(usize),
+}
+
+impl Quorum {
+    /// Number of ready items required out of `count` items
+    pub fn needed(&self, count: usize) -> usize {
+        match self {
+            Self::All => count,
+            Self::Any => count.min(1),
+            Self::AtLeast(at_least) => *at_least,
+        }
+    }
+}
+
+/// Items which are waited for together
+///
+/// The group is done once its quorum of required items is ready,
+/// the remaining items are no longer waited for then.
+/// When no item is required, all items are waited for
+/// and none of them is missing.
+///
+/// ```
+/// use std::time::Duration;
+/// use wait_for_them::{Group, Quorum, Status, TargetResult};
+///
+/// let group = Group {
+///     name: Some("cache".into()),
+///     quorum: Quorum::Any,
+///     targets: vec!["redis-a:6379".parse().unwrap(), "redis-b:6379".parse().unwrap()],
+/// };
+/// let cancelled = TargetResult::cancelled(group.targets[0].to_check.clone(), Duration::ZERO);
+/// let ready = TargetResult {
+///     status: Status::Ready,
+///     ..TargetResult::cancelled(group.targets[1].to_check.clone(), Duration::ZERO)
+/// };
+/// assert_eq!(group.missing(&[cancelled, ready]), 0);
+/// ```
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Group {
+    pub name: Option<String>,
+    pub quorum: Quorum,
+    pub targets: Vec<Target>,
+}
+
+impl Group {
+    /// Indexes of items which count towards the quorum
+    fn counted(&self) -> Vec<usize> {
+        let required: Vec<usize> = (0..self.targets.len())
+            .filter(|idx| self.targets[*idx].options.is_required())
+            .collect();
+        if required.is_empty() {
+            (0..self.targets.len()).collect()
+        } else {
+            required
+        }
+    }
+
+    /// Number of ready items missing to reach the quorum
+    ///
+    /// `results` are the results of [`wait_for_groups`](crate::wait_for_groups) for this group.
+    pub fn missing(&self, results: &[TargetResult]) -> usize {
+        // failures of optional items are ignored
+        if !self.targets.iter().any(|e| e.options.is_required()) {
+            return 0;
+        }
+        let counted = self.counted();
+        let ready = counted
+            .iter()
+            .filter(|idx| results.get(**idx).is_some_and(TargetResult::is_ready))
+            .count();
+        self.quorum.needed(counted.len()).saturating_sub(ready)
+    }
+
+    /// Waits till the quorum is reached, till all the counted items are done
+    /// or till `cancel` is done
+    ///
+    /// The items which are not done by then are reported as cancelled.
+    pub(crate) async fn wait(
+        &self,
+        futures: Vec<WaitFuture>,
+        instant: Instant,
+        cancel: impl Future<Output = ()>,
+    ) -> Vec<TargetResult> {
+        let counted = self.counted();
+        let needed = self.quorum.needed(counted.len());
+        let mut pending = counted.len();
+        let mut ready = 0;
+
+        let mut results = vec![None; futures.len()];
+        let mut running: FuturesUnordered<_> = futures
+            .into_iter()
+            .enumerate()
+            .map(|(idx, future)| async move { (idx, future.await) })
+            .collect();
+        let mut cancel = std::pin::pin!(cancel);
+        while ready < needed && pending > 0 {
+            let next = tokio::select! {
+                next = running.next() => next,
+                () = &mut cancel => None,
+            };
+            let Some((idx, result)) = next else {
+                break;
+            };
+            if counted.contains(&idx) {
+                pending -= 1;
+                if result.is_ready() {
+                    ready += 1;
+                }
+            }
+            results[idx] = Some(result);
+        }
+        // the remaining items are cancelled by dropping them
+        results
+            .into_iter()
+            .zip(&self.targets)
+            .map(|(result, target)| {
+                result.unwrap_or_else(|| {
+                    TargetResult::cancelled(target.to_check.clone(), instant.elapsed())
+                })
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Quorum {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "any" => Ok(Self::Any),
+            _ => match s.parse() {
+                Ok(0) | Err(_) => Err(format!("'{s}' is not one of all, any or a positive number")),
+                Ok(at_least) => Ok(Self::AtLeast(at_least)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Quorum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => "all".fmt(f),
+            Self::Any => "any".fmt(f),
+            Self::AtLeast(at_least) => at_least.fmt(f),
+        }
+    }
+}
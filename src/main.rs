@@ -1,8 +1,12 @@
 mod command;
 mod options;
 
-use std::{env, process::exit, time::Instant};
-use wait_for_them::wait_for_them;
+use std::{
+    env,
+    process::exit,
+    time::{Duration, Instant},
+};
+use wait_for_them::{report::Report, Output, Waiter};
 
 // For some reason these "deps" are required with `-D unused-crate-dependencies`
 #[cfg(feature = "http")]
@@ -18,24 +22,145 @@ use hyper_tls as _;
 use hyper_util as _;
 #[cfg(feature = "ui")]
 use indicatif as _;
+#[cfg(unix)]
+use libc as _;
+#[cfg(any(feature = "tls", feature = "k8s"))]
+use native_tls as _;
 use regex as _;
+#[cfg(feature = "config")]
+use serde as _;
+#[cfg(any(
+    feature = "docker",
+    feature = "k8s",
+    feature = "config",
+    feature = "json"
+))]
+use serde_json as _;
+#[cfg(any(feature = "config", feature = "compose"))]
+use serde_norway as _;
+#[cfg(feature = "tls")]
+use sha2 as _;
+#[cfg(feature = "tls")]
+use tokio_native_tls as _;
+#[cfg(feature = "ws")]
+use tokio_tungstenite as _;
+#[cfg(feature = "config")]
+use toml as _;
+#[cfg(feature = "tls")]
+use x509_parser as _;
 
 fn print_help(error: String) {
     let first_line = if cfg!(feature = "http") {
-        "wait-for-them [-t timeout] [-s] host:port|url [host:port|url [host:port|url...]] [-- command [arg [arg...]]"
+        "wait-for-them [-t timeout] [-s] [-o output] [--report format] [--stable count] [--stable-for time] [--retry policy] [--attempt-timeout time] [--any|--at-least n] [--group name=quorum:item[,item...]] [--config file] [--targets-from file] [--from-compose file [--service name]] [-] host:port|url [host:port|url [host:port|url...]] [-- command [arg [arg...]]"
     } else {
-        "wait-for-them [-t timeout] [-s] host:port [host:port [host:port...]] [-- command [arg [arg...]]"
+        "wait-for-them [-t timeout] [-s] [-o output] [--report format] [--stable count] [--stable-for time] [--retry policy] [--attempt-timeout time] [--any|--at-least n] [--group name=quorum:item[,item...]] [--config file] [--targets-from file] [--from-compose file [--service name]] [-] host:port [host:port [host:port...]] [-- command [arg [arg...]]"
     };
+    let config = if cfg!(feature = "config") {
+        "
+    --config FILE  load items and options from .toml, .yaml, .yml or .json file
+        options on the command line take precedence"
+    } else {
+        ""
+    };
+    let output = if cfg!(feature = "json") {
+        "
+    -o | --output text|json|ndjson  display the results as JSON array at the end
+        or one JSON object per line for each event"
+    } else {
+        ""
+    };
+    let compose = if cfg!(feature = "compose") {
+        "
+    --from-compose FILE  wait for the ports and healthchecks of docker compose services
+    --service NAME  only for the services NAME depends on"
+    } else {
+        ""
+    };
+    let mut targets = vec!["host:port  wait till the port is opened"];
+    if cfg!(feature = "http") {
+        targets.push("http(s)://host[:port][/path]  wait till 200 status is returned");
+        targets.push(
+            "metric+http(s)://host[:port]/path?name=METRIC[{label=value}]&value=N[&op=eq|ne|lt|le|gt|ge]
+            wait till prometheus metric satisfies the comparison",
+        );
+    }
+    if cfg!(feature = "tls") {
+        targets.push(
+            "tls://host:port[?sni=NAME&name=NAME&days=DAYS&fingerprint=SHA256&ca=PEM&insecure&starttls=PROTOCOL]
+            wait till TLS handshake succeeds and the certificate matches
+            PROTOCOL is one of smtp, imap, pop3, ldap or postgres",
+        );
+    }
+    if cfg!(feature = "grpc") {
+        targets.push("grpc(s)://host:port[/service]  wait till gRPC health check returns SERVING");
+    }
+    if cfg!(feature = "ws") {
+        targets.push(
+            "ws(s)://host[:port][/path][#send=MESSAGE&expect=REGEX]
+            wait till WebSocket connection is upgraded (and a matching message is received)",
+        );
+    }
+    if cfg!(feature = "mqtt") {
+        targets.push(
+            "mqtt://[user:password@]host[:port][?version=3.1.1|5&client_id=ID]
+            wait till MQTT broker accepts the connection",
+        );
+    }
+    targets.push("exec:COMMAND  wait till shell command exits with status code 0");
+    targets.push(
+        "log://PATH?match=REGEX[&from=start]  wait till a matching line is appended to the file",
+    );
+    targets.push(
+        "pid:PIDFILE|PID[?exited]  wait till the process is running (or exited)
+            use pid:./NUMBER for a PID file named as a number
+        process:NAME[?exited]  wait till a process with the name is running (or none is)",
+    );
+    if cfg!(feature = "docker") {
+        targets.push("docker://CONTAINER  wait till docker container is healthy (or running)");
+    }
+    if cfg!(feature = "k8s") {
+        targets.push(
+            "k8s://NAMESPACE/deployment|statefulset|pod|job/NAME
+            wait till kubernetes resource is ready (or the job completes)",
+        );
+    }
     println!(
         "{error}Usage:
     {first_line}
-    -s | --silent  don't display any output
-    -t TIMEOUT | --timeout TIMEOUT  in milliseconds
+    -s | --silent  don't display any output{output}
+    --report junit=PATH|tap[=PATH]  write JUnit XML or TAP report with a test case per item
+        TAP is printed instead of the progress when PATH is not set
+    -t TIMEOUT | --timeout TIMEOUT  e.g. 500ms, 30s, 2m or 1h30m
+    --stable COUNT  require COUNT consecutive successful attempts
+    --stable-for TIME  require the attempts to keep succeeding for TIME
+    --retry POLICY  delays between failed attempts (fixed:100ms by default)
+        fixed:INTERVAL | exponential:INITIAL:MAX | jitter:INITIAL:MAX
+    --attempt-timeout TIME  time limit of a single attempt
+    --any  wait till any of the items is verified
+    --at-least N  wait till N of the items are verified
+    --group NAME=QUORUM:ITEM[,ITEM...]  items with their own quorum
+        QUORUM is one of all, any or a number{config}
+    --targets-from FILE  read items from FILE, one per line
+    -  read items from stdin, one per line{compose}
         Wait till all links are verified
+        TIME without a unit is in milliseconds
 
     wait-for-them -h | --help
         Display help
+
+Supported items:
+        {}
+
+Each item may be followed by @option=value[,option=value] to override the options above
+    timeout=TIME  required=true|false  stable=COUNT  stable_for=TIME
+    retry=POLICY  attempt_timeout=TIME
+    name=NAME  after=NAME  check the item once the named item or group is ready
+
+Environment:
+    WAIT_FOR_THEM_TARGETS  items added to the other ones, one per line
+    WAIT_FOR_THEM_TIMEOUT  used when -t is not set
 ",
+        targets.join("\n        "),
     );
 }
 
@@ -45,10 +170,15 @@ async fn main() {
     args.remove(0);
 
     let options::Options {
-        to_check,
+        targets,
+        quorum,
+        groups,
         timeout,
         command,
         silent,
+        output,
+        reports,
+        warnings,
     } = match options::parse(args) {
         Ok(options) => options,
         Err(options::Action::Failed(message)) => {
@@ -65,14 +195,58 @@ async fn main() {
         }
     };
 
+    if !silent {
+        for warning in warnings {
+            eprintln!("Warning: {warning}");
+        }
+    }
+
     let instant = Instant::now();
 
-    let res = wait_for_them(&to_check, timeout, Some(instant), silent).await;
+    // stdout is taken by the TAP report instead of the progress
+    let tap = reports.contains(&Report::Tap(None));
+    let output = if silent || tap {
+        Output::Silent
+    } else {
+        output
+    };
+    // only the progress is reported as text
+    let text = output == Output::Progress;
+
+    let mut builder = Waiter::builder()
+        .targets(targets)
+        .quorum(quorum)
+        .groups(groups)
+        .output(output)
+        .start_time(instant);
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(Duration::from_millis(timeout));
+    }
+    let waiter = match builder.build() {
+        Ok(waiter) => waiter,
+        Err(err) => {
+            print_help(err);
+            exit(999);
+        }
+    };
+    let groups = waiter.groups().to_vec();
+    let res = waiter.wait().await;
+
+    for report in reports {
+        if let Err(err) = report.write(&groups, &res) {
+            eprintln!("{err}");
+            exit(999);
+        }
+    }
 
-    let err_count = res.iter().filter(|e| e.is_none()).count();
+    let err_count: usize = groups
+        .iter()
+        .zip(&res)
+        .map(|(group, res)| group.missing(res))
+        .sum();
 
     if err_count == 0 {
-        if !silent {
+        if text {
             println!(
                 "All items verified in {:.3} seconds.",
                 instant.elapsed().as_secs_f32()
@@ -80,7 +254,7 @@ async fn main() {
         }
 
         if let Some(mut cmd) = command {
-            if !silent {
+            if text {
                 println!("Starting '{}'", cmd.join(" "));
             }
             let executable = cmd.remove(0);
@@ -91,7 +265,7 @@ async fn main() {
                 Err(_) => exit(999),
             }
         }
-    } else if !silent {
+    } else if text {
         println!(
             "Failed verify all items in {:.3} seconds.",
             instant.elapsed().as_secs_f32()
@@ -0,0 +1,214 @@
+use std::{fmt::Write, path::PathBuf, str::FromStr, time::Duration};
+
+use crate::{Group, Status, TargetResult};
+
+/// Name of the test suite of the items which are not in any named group
+const DEFAULT_SUITE: &str = "wait-for-them";
+
+/// Report of the results and where it is written
+///
+/// it may be parsed from string
+/// ```
+/// use wait_for_them::report::Report;
+///
+/// assert_eq!("junit=report.xml".parse(), Ok(Report::Junit("report.xml".into())));
+/// assert_eq!("tap".parse(), Ok(Report::Tap(None)));
+/// assert!("junit".parse::<Report>().is_err());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Report {
+    /// JUnit XML file
+    Junit(PathBuf),
+    /// Test Anything Protocol written to the file or to stdout when `None`
+    Tap(Option<PathBuf>),
+}
+
+impl FromStr for Report {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some(("junit", path)) if !path.is_empty() => Ok(Self::Junit(path.into())),
+            Some(("tap", path)) if !path.is_empty() => Ok(Self::Tap(Some(path.into()))),
+            None if s == "tap" => Ok(Self::Tap(None)),
+            _ => Err(format!("'{s}' is not one of junit=PATH, tap or tap=PATH")),
+        }
+    }
+}
+
+impl Report {
+    /// Renders the results of the groups returned by [`wait_for_groups`](crate::wait_for_groups)
+    pub fn render(&self, groups: &[Group], results: &[Vec<TargetResult>]) -> String {
+        match self {
+            Self::Junit(_) => junit(groups, results),
+            Self::Tap(_) => tap(groups, results),
+        }
+    }
+
+    /// Renders the results and writes them to the file or to stdout
+    pub fn write(&self, groups: &[Group], results: &[Vec<TargetResult>]) -> Result<(), String> {
+        let content = self.render(groups, results);
+        match self {
+            Self::Junit(path) | Self::Tap(Some(path)) => std::fs::write(path, content)
+                .map_err(|err| format!("Failed to write '{}': {err}", path.display())),
+            Self::Tap(None) => {
+                print!("{content}");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Reason of a failed item
+fn failure_message(result: &TargetResult) -> String {
+    result
+        .last_error
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_else(|| "not ready".to_string())
+}
+
+/// The items are checked concurrently, so a suite takes as long as its slowest item
+fn duration(results: &[TargetResult]) -> Duration {
+    results.iter().map(|e| e.elapsed).max().unwrap_or_default()
+}
+
+fn count(results: &[TargetResult], status: Status) -> usize {
+    results.iter().filter(|e| e.status == status).count()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.chars()
+        // control characters are not allowed in XML 1.0
+        .filter(|e| !e.is_control() || "\t\n\r".contains(*e))
+        .fold(String::new(), |mut escaped, e| {
+            match e {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                e => escaped.push(e),
+            }
+            escaped
+        })
+}
+
+/// JUnit XML with a test suite per group and a test case per item
+///
+/// Failed items carry the last error as the failure message,
+/// items which were no longer waited for are skipped.
+pub fn junit(groups: &[Group], results: &[Vec<TargetResult>]) -> String {
+    let all: Vec<TargetResult> = results.iter().flatten().cloned().collect();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"{DEFAULT_SUITE}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+        all.len(),
+        count(&all, Status::Failed),
+        count(&all, Status::Cancelled),
+        duration(&all).as_secs_f64(),
+    );
+    let suites = groups
+        .iter()
+        .zip(results)
+        .filter(|(_, results)| !results.is_empty());
+    for (group, results) in suites {
+        let suite = xml_escape(group.name.as_deref().unwrap_or(DEFAULT_SUITE));
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{suite}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+            results.len(),
+            count(results, Status::Failed),
+            count(results, Status::Cancelled),
+            duration(results).as_secs_f64(),
+        );
+        for result in results {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{suite}\" time=\"{:.3}\"",
+                xml_escape(&result.to_check.to_string()),
+                result.elapsed.as_secs_f64(),
+            );
+            match result.status {
+                Status::Ready => xml.push_str("/>\n"),
+                Status::Failed => {
+                    let message = xml_escape(&failure_message(result));
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <failure message=\"{message}\">{message} ({} attempts)</failure>\n    </testcase>",
+                        result.attempts,
+                    );
+                }
+                Status::Cancelled => xml.push_str(
+                    ">\n      <skipped message=\"no longer waited for\"/>\n    </testcase>\n",
+                ),
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// Double quoted YAML string
+fn yaml_quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for e in text.chars() {
+        match e {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            e if e.is_control() => {
+                let _ = write!(quoted, "\\x{:02x}", e as u32);
+            }
+            e => quoted.push(e),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// TAP version 13 with a test point per item
+///
+/// Each test point carries the duration and the number of attempts as YAML diagnostics,
+/// failed items also the last error. Items which were no longer waited for are skipped.
+pub fn tap(groups: &[Group], results: &[Vec<TargetResult>]) -> String {
+    let total: usize = results.iter().map(Vec::len).sum();
+    let mut tap = format!("TAP version 13\n1..{total}\n");
+    let items = groups
+        .iter()
+        .zip(results)
+        .flat_map(|(group, results)| results.iter().map(move |e| (group.name.as_deref(), e)));
+    for (idx, (group, result)) in items.enumerate() {
+        let target = result.to_check.to_string();
+        // `#` would start a directive
+        let description = match group {
+            Some(group) => format!("{group}: {target}"),
+            None => target,
+        }
+        .replace('#', "\\#");
+        let _ = match result.status {
+            Status::Ready => writeln!(tap, "ok {} - {description}", idx + 1),
+            Status::Failed => writeln!(tap, "not ok {} - {description}", idx + 1),
+            Status::Cancelled => writeln!(
+                tap,
+                "ok {} - {description} # SKIP no longer waited for",
+                idx + 1
+            ),
+        };
+        let _ = writeln!(
+            tap,
+            "  ---\n  duration_ms: {}\n  attempts: {}",
+            result.elapsed.as_millis(),
+            result.attempts,
+        );
+        if result.status == Status::Failed {
+            let _ = writeln!(tap, "  message: {}", yaml_quote(&failure_message(result)));
+        }
+        tap.push_str("  ...\n");
+    }
+    tap
+}
@@ -0,0 +1,159 @@
+use std::{error::Error, fmt, io, time::Duration};
+
+use crate::ToCheck;
+
+/// Reason why an item is not ready
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CheckError {
+    /// the connection was refused
+    Refused,
+    /// the hostname could not be resolved
+    Dns(String),
+    /// other failures to connect or to communicate
+    Io(String),
+    /// the TLS handshake or the verification of the certificate failed
+    Tls(String),
+    /// unexpected HTTP status code
+    Status(u16),
+    /// no response within the time limit of a single attempt
+    Timeout(Duration),
+    /// the item or the group this item depends on is not ready
+    Dependency(String),
+    /// the item timed out while its dependencies were not ready yet
+    Waiting(Vec<String>),
+    /// the item responded, but it is not ready, e.g. an unhealthy container
+    NotReady(String),
+}
+
+#[cfg(feature = "http")]
+impl CheckError {
+    /// Finds the cause of an error such as the one of an http client
+    pub(crate) fn from_chain(err: &(dyn Error + 'static)) -> Self {
+        let mut source = Some(err);
+        while let Some(err) = source {
+            if let Some(err) = err.downcast_ref::<io::Error>() {
+                if err.kind() == io::ErrorKind::ConnectionRefused {
+                    return Self::Refused;
+                }
+            }
+            if err.to_string().starts_with("dns error") {
+                return Self::Dns(chain_message(err));
+            }
+            source = err.source();
+        }
+        Self::Io(chain_message(err))
+    }
+}
+
+/// Joins the messages of the error and its sources
+#[cfg(feature = "http")]
+fn chain_message(err: &(dyn Error + 'static)) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        let cause = err.to_string();
+        if !message.contains(&cause) {
+            message = format!("{message}: {cause}");
+        }
+        source = err.source();
+    }
+    message
+}
+
+impl From<io::Error> for CheckError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::ConnectionRefused => Self::Refused,
+            _ => Self::Io(err.to_string()),
+        }
+    }
+}
+
+/// Reasons reported by the checks which are not ready
+impl From<String> for CheckError {
+    fn from(reason: String) -> Self {
+        Self::NotReady(reason)
+    }
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Refused => write!(f, "connection refused"),
+            Self::Dns(err) => write!(f, "failed to resolve: {err}"),
+            Self::Io(err) | Self::NotReady(err) => err.fmt(f),
+            Self::Tls(err) => write!(f, "tls: {err}"),
+            Self::Status(status) => write!(f, "status {status}"),
+            Self::Timeout(timeout) => write!(f, "no response in {} ms", timeout.as_millis()),
+            Self::Dependency(name) => write!(f, "'{name}' is not ready"),
+            Self::Waiting(names) => write!(f, "waiting on {}", names.join(", ")),
+        }
+    }
+}
+
+impl Error for CheckError {}
+
+/// Final state of an item
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Status {
+    Ready,
+    Failed,
+    /// the item was no longer waited for, e.g. once the quorum of its group was reached
+    Cancelled,
+}
+
+/// Result of waiting for a single item
+///
+/// ```
+/// use std::time::Duration;
+/// use wait_for_them::{Status, TargetResult};
+///
+/// let result = TargetResult {
+///     status: Status::Ready,
+///     elapsed: Duration::from_millis(120),
+///     ..TargetResult::cancelled("localhost:8080".parse().unwrap(), Duration::ZERO)
+/// };
+/// assert_eq!(result.elapsed_ms(), Some(120));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct TargetResult {
+    pub to_check: ToCheck,
+    pub status: Status,
+    /// time since the start till the item was done
+    pub elapsed: Duration,
+    pub attempts: u32,
+    /// reason of the first failed attempt
+    pub first_error: Option<CheckError>,
+    /// reason of the last failed attempt
+    pub last_error: Option<CheckError>,
+    /// time since the start when the item last changed between failing and succeeding
+    /// or when it was done
+    pub last_change: Duration,
+    /// details of the successful attempt
+    pub details: Option<String>,
+}
+
+impl TargetResult {
+    /// Result of an item which was no longer waited for
+    pub fn cancelled(to_check: ToCheck, elapsed: Duration) -> Self {
+        Self {
+            to_check,
+            status: Status::Cancelled,
+            elapsed,
+            attempts: 0,
+            first_error: None,
+            last_error: None,
+            last_change: elapsed,
+            details: None,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.status == Status::Ready
+    }
+
+    /// Elapsed time in milis of a ready item, the way [`wait_for_them`](crate::wait_for_them) reports it
+    pub fn elapsed_ms(&self) -> Option<u64> {
+        self.is_ready().then_some(self.elapsed.as_millis() as u64)
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::parse_duration;
+
+const DEFAULT_INTERVAL: u64 = 100_u64;
+
+/// Delay between the attempts of a single item
+///
+/// it may be parsed from `fixed:<interval>`, `exponential:<initial>:<max>`
+/// or `jitter:<initial>:<max>` where the times are parsed using
+/// [`parse_duration`](crate::parse_duration).
+///
+/// ```
+/// use std::time::Duration;
+/// use wait_for_them::RetryPolicy;
+///
+/// let policy: RetryPolicy = "exponential:100:5000".parse().unwrap();
+/// assert_eq!(
+///     policy,
+///     RetryPolicy::Exponential {
+///         initial: Duration::from_millis(100),
+///         max: Duration::from_millis(5000),
+///     }
+/// );
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RetryPolicy {
+    /// Same delay after each failed attempt
+    Fixed(Duration),
+    /// Delay is doubled after each failed attempt up to `max`
+    Exponential { initial: Duration, max: Duration },
+    /// Random delay between `initial` and three times the previous delay up to `max`
+    ///
+    /// Spreads the attempts of many clients waiting for the same service.
+    DecorrelatedJitter { initial: Duration, max: Duration },
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::Fixed(Duration::from_millis(DEFAULT_INTERVAL))
+    }
+}
+
+impl RetryPolicy {
+    /// Delay after a successful attempt of an item which is not stable yet
+    pub(crate) fn interval(&self) -> Duration {
+        match self {
+            Self::Fixed(interval) => *interval,
+            Self::Exponential { initial, .. } | Self::DecorrelatedJitter { initial, .. } => {
+                *initial
+            }
+        }
+    }
+
+    pub(crate) fn backoff(&self) -> PolicyBackoff {
+        PolicyBackoff {
+            policy: *self,
+            previous: None,
+            seed: seed(),
+        }
+    }
+}
+
+/// Generates delays of consecutive failed attempts of a single item
+///
+/// Implement it to use other policy than the ones of [`RetryPolicy`],
+/// see [`WaiterBuilder::backoff`](crate::WaiterBuilder::backoff).
+///
+/// ```
+/// use std::time::Duration;
+/// use wait_for_them::Backoff;
+///
+/// /// Waits one more second after each failed attempt
+/// struct Linear(u32);
+///
+/// impl Backoff for Linear {
+///     fn next_delay(&mut self) -> Duration {
+///         self.0 += 1;
+///         Duration::from_secs(self.0.into())
+///     }
+///
+///     fn reset(&mut self) {
+///         self.0 = 0;
+///     }
+/// }
+/// ```
+pub trait Backoff: Send {
+    /// Delay after a failed attempt
+    fn next_delay(&mut self) -> Duration;
+
+    /// Starts again with the initial delay, called after a successful attempt
+    fn reset(&mut self) {}
+}
+
+/// Delays of a [`RetryPolicy`]
+pub(crate) struct PolicyBackoff {
+    policy: RetryPolicy,
+    previous: Option<Duration>,
+    seed: u64,
+}
+
+impl Backoff for PolicyBackoff {
+    fn next_delay(&mut self) -> Duration {
+        let delay = match (self.policy, self.previous) {
+            (RetryPolicy::Fixed(interval), _) => interval,
+            (RetryPolicy::Exponential { initial, .. }, None) => initial,
+            (RetryPolicy::Exponential { max, .. }, Some(previous)) => (previous * 2).min(max),
+            (RetryPolicy::DecorrelatedJitter { initial, max }, previous) => {
+                let upper = previous.unwrap_or(initial) * 3;
+                let range = upper.saturating_sub(initial).as_millis() as u64;
+                let jitter = Duration::from_millis(self.random() % (range + 1));
+                (initial + jitter).min(max)
+            }
+        };
+        self.previous = Some(delay);
+        delay
+    }
+
+    fn reset(&mut self) {
+        self.previous = None;
+    }
+}
+
+impl PolicyBackoff {
+    /// xorshift64* generator, good enough to spread the attempts
+    fn random(&mut self) -> u64 {
+        self.seed ^= self.seed >> 12;
+        self.seed ^= self.seed << 25;
+        self.seed ^= self.seed >> 27;
+        self.seed.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+/// Number of the backoffs created so far
+static BACKOFFS: AtomicU64 = AtomicU64::new(0);
+
+/// Seed which differs between processes and items
+fn seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|e| e.as_nanos() as u64)
+        .unwrap_or_default();
+    let count = BACKOFFS.fetch_add(1, Ordering::Relaxed);
+    // splitmix64 finalizer, so that consecutive backoffs get unrelated seeds
+    let mut seed =
+        nanos ^ ((std::process::id() as u64) << 32) ^ count.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    seed = (seed ^ (seed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    seed = (seed ^ (seed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (seed ^ (seed >> 31)) | 1
+}
+
+impl FromStr for RetryPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let duration = |value: &str| parse_duration(value).map_err(|err| format!("'{s}', {err}"));
+        let policy = match s.split(':').collect::<Vec<_>>().as_slice() {
+            ["fixed", interval] => Self::Fixed(duration(interval)?),
+            ["exponential", initial, max] => Self::Exponential {
+                initial: duration(initial)?,
+                max: duration(max)?,
+            },
+            ["jitter", initial, max] => Self::DecorrelatedJitter {
+                initial: duration(initial)?,
+                max: duration(max)?,
+            },
+            _ => {
+                return Err(format!(
+                    "'{s}' doesn't match fixed:<interval>, exponential:<initial>:<max> or jitter:<initial>:<max> pattern"
+                ))
+            }
+        };
+        match policy {
+            Self::Exponential { initial, max } | Self::DecorrelatedJitter { initial, max }
+                if initial.is_zero() || max < initial =>
+            {
+                Err(format!(
+                    "'{s}', initial has to be positive and not exceed max"
+                ))
+            }
+            policy => Ok(policy),
+        }
+    }
+}
+
+impl fmt::Display for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(interval) => write!(f, "fixed:{}", interval.as_millis()),
+            Self::Exponential { initial, max } => {
+                write!(f, "exponential:{}:{}", initial.as_millis(), max.as_millis())
+            }
+            Self::DecorrelatedJitter { initial, max } => {
+                write!(f, "jitter:{}:{}", initial.as_millis(), max.as_millis())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backoff, RetryPolicy};
+    use std::time::Duration;
+
+    fn delays(policy: &str, count: usize) -> Vec<u64> {
+        let mut backoff = policy.parse::<RetryPolicy>().unwrap().backoff();
+        (0..count)
+            .map(|_| backoff.next_delay().as_millis() as u64)
+            .collect()
+    }
+
+    #[test]
+    fn fixed() {
+        assert_eq!(delays("fixed:250ms", 3), vec![250, 250, 250]);
+    }
+
+    #[test]
+    fn exponential() {
+        assert_eq!(
+            delays("exponential:100ms:1s", 6),
+            vec![100, 200, 400, 800, 1000, 1000]
+        );
+
+        let mut backoff = "exponential:100ms:1s"
+            .parse::<RetryPolicy>()
+            .unwrap()
+            .backoff();
+        backoff.next_delay();
+        backoff.next_delay();
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn jitter() {
+        let mut previous = 100;
+        for delay in delays("jitter:100ms:2s", 100) {
+            assert!(delay >= 100);
+            assert!(delay <= (previous * 3).min(2000));
+            previous = delay;
+        }
+        // the delays reach the cap
+        assert!(delays("jitter:100ms:300ms", 100).contains(&300));
+    }
+
+    #[test]
+    fn seeds() {
+        let policy: RetryPolicy = "jitter:1ms:1s".parse().unwrap();
+        let seeds: Vec<u64> = (0..100).map(|_| policy.backoff().seed).collect();
+        for (idx, seed) in seeds.iter().enumerate() {
+            assert!(!seeds[idx + 1..].contains(seed));
+        }
+        // backoffs created at the same time don't produce the same delays
+        assert_ne!(delays("jitter:1ms:1s", 20), delays("jitter:1ms:1s", 20));
+    }
+}
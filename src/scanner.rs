@@ -3,62 +3,100 @@ use bytes::Bytes;
 #[cfg(feature = "http")]
 use http_body_util::Empty;
 #[cfg(feature = "http")]
-use hyper::StatusCode;
+use hyper::{body::Incoming, Response, StatusCode};
 #[cfg(feature = "http")]
 use hyper_tls::HttpsConnector;
 #[cfg(feature = "http")]
-use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::TokioExecutor,
+};
 #[cfg(feature = "ui")]
 use indicatif::{MultiProgress, ProgressBar, ProgressFinish, ProgressStyle};
-#[cfg(feature = "ui")]
+#[cfg(any(feature = "ui", feature = "json"))]
 use std::sync::Arc;
+#[cfg(feature = "json")]
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{
     future::Future,
+    net::SocketAddr,
     pin::Pin,
     time::{Duration, Instant},
 };
 #[cfg(feature = "ui")]
 use tokio::sync::Mutex;
-use tokio::{self, net::TcpStream, time};
+use tokio::{
+    self,
+    net::{self, TcpStream},
+    time,
+};
 
-use crate::ToCheck;
+use crate::{
+    dependency::Dependency, retry::Backoff, CheckError, Status, Target, TargetOptions,
+    TargetResult, ToCheck,
+};
+
+#[cfg(feature = "docker")]
+pub mod docker;
+pub mod exec;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "k8s")]
+pub mod k8s;
+pub mod log;
+#[cfg(feature = "http")]
+pub mod metric;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod process;
+#[cfg(feature = "tls")]
+pub mod starttls;
+#[cfg(feature = "tls")]
+pub mod tls;
+#[cfg(feature = "ws")]
+pub mod ws;
 
-const RETRY_TIMEOUT: u64 = 100_u64;
 const NO_RESPONSE_TIMEOUT: u64 = 1000_u64;
 
+/// Resolves once the item is ready or once it fails
+pub type WaitFuture = Pin<Box<dyn Future<Output = TargetResult> + Send>>;
+
 #[cfg(not(feature = "ui"))]
 pub fn wait(
-    hosts_ports_or_http_urls: &[ToCheck],
-    timeout: Option<u64>,
+    targets: &[Target],
+    after: Vec<Vec<Dependency>>,
+    backoffs: Vec<Box<dyn Backoff>>,
     instant: Instant,
-) -> Vec<Pin<Box<dyn Future<Output = Option<u64>>>>> {
-    hosts_ports_or_http_urls
+) -> Vec<WaitFuture> {
+    targets
         .iter()
-        .map(|to_check| {
+        .zip(after)
+        .zip(backoffs)
+        .map(|((target, after), backoff)| {
             let generator = ProgressGenerator {
-                to_check: to_check.clone(),
+                to_check: target.to_check.clone(),
                 instant,
+                streak: None,
             };
-            Wait::new(
-                to_check.clone(),
-                timeout.map(Duration::from_millis),
-                Box::new(generator),
-            )
-            .wait_future()
+            Wait::new(target, after, backoff, instant, Box::new(generator)).wait_future()
         })
         .collect()
 }
 
 #[cfg(feature = "ui")]
 pub fn wait(
-    hosts_ports_or_http_urls: &[ToCheck],
-    timeout: Option<u64>,
+    targets: &[Target],
+    after: Vec<Vec<Dependency>>,
+    backoffs: Vec<Box<dyn Backoff>>,
     instant: Instant,
-) -> Vec<Pin<Box<dyn Future<Output = Option<u64>>>>> {
+) -> Vec<WaitFuture> {
     let multiple = MultiProgress::new();
-    hosts_ports_or_http_urls
+    targets
         .iter()
-        .map(|to_check| {
+        .zip(after)
+        .zip(backoffs)
+        .map(|((target, after), backoff)| {
+            let timeout = target.options.timeout.map(|e| e.as_millis() as u64);
             let pb = if let Some(timeout) = timeout {
                 multiple.add(ProgressBar::new(timeout).with_finish(ProgressFinish::AndLeave))
             } else {
@@ -66,22 +104,20 @@ pub fn wait(
             };
             let sty = if timeout.is_some() {
                 ProgressStyle::default_bar()
-                    .template(&format!(
-                        "{} {}",
-                        "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}", to_check
-                    ))
+                    .template(
+                        "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {prefix} {msg}",
+                    )
                     .unwrap()
                     .progress_chars("##-")
             } else {
                 ProgressStyle::default_spinner()
                     .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ")
-                    .template(&format!(
-                        "{} {}",
-                        "[{elapsed_precise}] {spinner} {msg}", to_check
-                    ))
+                    .template("[{elapsed_precise}] {spinner} {prefix} {msg}")
                     .unwrap()
             };
             pb.set_style(sty);
+            // targets may contain braces which would be parsed as a part of the template
+            pb.set_prefix(target.to_check.to_string());
             pb.set_message(" ");
             pb.tick();
 
@@ -89,148 +125,397 @@ pub fn wait(
                 instant,
                 progress: Arc::new(Mutex::new(pb)),
             };
-            Wait::new(
-                to_check.clone(),
-                timeout.map(Duration::from_millis),
-                Box::new(generator),
-            )
-            .wait_future()
+            Wait::new(target, after, backoff, instant, Box::new(generator)).wait_future()
         })
         .collect()
 }
 
 pub fn wait_silent(
-    hosts_ports_or_http_urls: &[ToCheck],
-    timeout: Option<u64>,
+    targets: &[Target],
+    after: Vec<Vec<Dependency>>,
+    backoffs: Vec<Box<dyn Backoff>>,
     instant: Instant,
-) -> Vec<Pin<Box<dyn Future<Output = Option<u64>>>>> {
-    hosts_ports_or_http_urls
+) -> Vec<WaitFuture> {
+    targets
         .iter()
-        .map(|to_check| {
+        .zip(after)
+        .zip(backoffs)
+        .map(|((target, after), backoff)| {
             let progress = SilentGenerator::new(instant);
-            Wait::new(
-                to_check.clone(),
-                timeout.map(Duration::from_millis),
-                Box::new(progress),
-            )
-            .wait_future()
+            Wait::new(target, after, backoff, instant, Box::new(progress)).wait_future()
         })
         .collect()
 }
 
+/// Items reporting their progress as JSON, `stream` prints the events as they happen
+///
+/// Records of the items are returned so that the items which were
+/// no longer waited for can be reported as well, see [`report_json`].
+#[cfg(feature = "json")]
+pub fn wait_json(
+    targets: &[Target],
+    after: Vec<Vec<Dependency>>,
+    backoffs: Vec<Box<dyn Backoff>>,
+    instant: Instant,
+    stream: bool,
+) -> (Vec<WaitFuture>, Vec<SharedRecord>) {
+    targets
+        .iter()
+        .zip(after)
+        .zip(backoffs)
+        .map(|((target, after), backoff)| {
+            let generator = JsonGenerator::new(&target.to_check, instant, stream);
+            let record = generator.record.clone();
+            (
+                Wait::new(target, after, backoff, instant, Box::new(generator)).wait_future(),
+                record,
+            )
+        })
+        .unzip()
+}
+
+/// Prints the records once the waiting is over
+///
+/// Items which are still pending were no longer waited for and are reported as cancelled.
+/// All the records are printed as a single JSON array unless `stream` is set,
+/// the other records were already printed as events in that case.
+#[cfg(feature = "json")]
+pub fn report_json(records: &[SharedRecord], stream: bool) {
+    let mut entries = vec![];
+    for record in records {
+        let mut record = record.lock().unwrap();
+        if record.status == "pending" {
+            record.status = "cancelled";
+            if stream {
+                println!("{}", record.event(record.status));
+            }
+        }
+        entries.push(record.to_json());
+    }
+    if !stream {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).unwrap_or_default()
+        );
+    }
+}
+
+/// Outcome of a single attempt
+///
+/// `Ok` means that the item is ready and may carry details to be displayed,
+/// `Err` contains the reason why the item is not ready yet.
+pub type Attempt = Result<Option<String>, CheckError>;
+
+/// Performs a single attempt to verify an item
+pub trait Probe: Send {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>>;
+
+    /// Time limit of a single attempt
+    fn attempt_timeout(&self) -> Duration {
+        Duration::from_millis(NO_RESPONSE_TIMEOUT)
+    }
+}
+
+struct TcpProbe {
+    domain: String,
+    port: u16,
+}
+
+/// Resolves the host and connects to it, so that resolution failures are told apart
+pub(crate) async fn connect(host: &str, port: u16) -> Result<TcpStream, CheckError> {
+    let addresses: Vec<SocketAddr> = net::lookup_host((host, port))
+        .await
+        .map_err(|err| CheckError::Dns(err.to_string()))?
+        .collect();
+    if addresses.is_empty() {
+        return Err(CheckError::Dns(format!("no address of '{host}'")));
+    }
+    Ok(TcpStream::connect(addresses.as_slice()).await?)
+}
+
+impl Probe for TcpProbe {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move { connect(&self.domain, self.port).await.map(|_| None) })
+    }
+}
+
+#[cfg(feature = "http")]
+struct HttpProbe {
+    client: Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
+    url: hyper::Uri,
+}
+
+#[cfg(feature = "http")]
+impl HttpProbe {
+    fn new(url: hyper::Uri) -> Self {
+        let https_or_http = HttpsConnector::new();
+        let client = Client::builder(TokioExecutor::new()).build(https_or_http);
+        Self { client, url }
+    }
+
+    async fn get(&self) -> Result<Response<Incoming>, CheckError> {
+        self.client
+            .get(self.url.clone())
+            .await
+            .map_err(|err| CheckError::from_chain(&err))
+    }
+}
+
+#[cfg(feature = "http")]
+impl Probe for HttpProbe {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move {
+            let resp = self.get().await?;
+            if resp.status() == StatusCode::OK {
+                Ok(None)
+            } else {
+                Err(CheckError::Status(resp.status().as_u16()))
+            }
+        })
+    }
+}
+
+fn probe(to_check: &ToCheck) -> Box<dyn Probe> {
+    match to_check {
+        ToCheck::HostnameAndPort(domain, port) => Box::new(TcpProbe {
+            domain: domain.clone(),
+            port: *port,
+        }),
+        #[cfg(feature = "http")]
+        ToCheck::HttpOrHttpsUrl(url) => Box::new(HttpProbe::new(url.clone())),
+        #[cfg(feature = "http")]
+        ToCheck::Metric(metric) => Box::new(metric::MetricProbe::new(metric)),
+        #[cfg(feature = "tls")]
+        ToCheck::Tls(tls) => Box::new(tls.clone()),
+        #[cfg(feature = "grpc")]
+        ToCheck::Grpc(grpc) => Box::new(grpc::GrpcProbe::new(grpc)),
+        #[cfg(feature = "ws")]
+        ToCheck::Ws(ws) => Box::new(ws::WsProbe::new(ws)),
+        #[cfg(feature = "mqtt")]
+        ToCheck::Mqtt(mqtt) => Box::new(mqtt::MqttProbe::new(mqtt)),
+        ToCheck::Exec(exec) => Box::new(exec.clone()),
+        ToCheck::Log(log) => Box::new(log::LogProbe::new(log)),
+        ToCheck::Process(process) => Box::new(process.clone()),
+        #[cfg(feature = "docker")]
+        ToCheck::Docker(docker) => Box::new(docker.clone()),
+        #[cfg(feature = "k8s")]
+        ToCheck::K8s(k8s) => Box::new(k8s::K8sProbe::new(k8s)),
+    }
+}
+
 struct Wait {
     to_check: ToCheck,
-    timeout: Option<Duration>,
+    instant: Instant,
+    probe: Box<dyn Probe>,
+    options: TargetOptions,
+    after: Vec<Dependency>,
+    backoff: Box<dyn Backoff>,
     generator: Box<dyn Generator>,
+    attempts: u32,
+    first_error: Option<CheckError>,
+    last_error: Option<CheckError>,
+    /// whether the last attempt succeeded and when that changed
+    last_change: Option<(bool, Instant)>,
 }
 
 impl Wait {
     pub fn new(
-        to_check: ToCheck,
-        timeout: Option<Duration>,
+        target: &Target,
+        after: Vec<Dependency>,
+        backoff: Box<dyn Backoff>,
+        instant: Instant,
         generator: Box<dyn Generator>,
     ) -> Self {
         Self {
-            to_check,
-            timeout,
+            to_check: target.to_check.clone(),
+            instant,
+            probe: probe(&target.to_check),
+            options: target.options.clone(),
+            after,
+            backoff,
             generator,
+            attempts: 0,
+            first_error: None,
+            last_error: None,
+            last_change: None,
         }
     }
 
-    async fn wait_for_connection_tcp(&mut self) {
+    /// Records the outcome of an attempt
+    fn record(&mut self, outcome: Result<(), CheckError>) {
+        let ok = outcome.is_ok();
+        if self.last_change.is_none_or(|(last, _)| last != ok) {
+            self.last_change = Some((ok, Instant::now()));
+        }
+        if let Err(err) = outcome {
+            self.first_error.get_or_insert_with(|| err.clone());
+            self.last_error = Some(err);
+        }
+    }
+
+    /// Waits till the items and groups this item depends on are ready
+    ///
+    /// Returns `false` when any of them failed.
+    async fn wait_for_dependencies(&mut self) -> bool {
+        let mut after = std::mem::take(&mut self.after);
+        let mut blocked = false;
         loop {
-            self.generator.generate_tick().await;
-            let ToCheck::HostnameAndPort(ref domain, port) = self.to_check else {
-                unreachable!()
-            };
-            let timeout = time::timeout(
-                Duration::from_millis(NO_RESPONSE_TIMEOUT),
-                TcpStream::connect((domain.as_str(), port)),
-            )
-            .await;
-            if timeout.is_err() {
-                continue;
-            }
-            if timeout.unwrap().is_err() {
-                time::sleep(Duration::from_millis(RETRY_TIMEOUT)).await;
-            } else {
+            // a closed channel means that the item is no longer waited for
+            let mut pending: Vec<&mut Dependency> = after
+                .iter_mut()
+                .filter(|e| e.state.borrow().is_none() && e.state.has_changed().is_ok())
+                .collect();
+            if pending.is_empty() {
                 break;
             }
-        }
-    }
+            blocked = true;
+            let names: Vec<String> = pending.iter().map(|e| e.name.clone()).collect();
+            self.last_error = Some(CheckError::Waiting(names.clone()));
+            self.generator.generate_waiting(names).await;
 
-    #[cfg(feature = "http")]
-    async fn wait_for_connection_http(&mut self) {
-        let https_or_http = HttpsConnector::new();
+            let changes = pending.iter_mut().map(|e| Box::pin(e.state.changed()));
+            let _ = futures::future::select_all(changes).await;
+        }
 
-        let client: Client<_, Empty<Bytes>> =
-            Client::builder(TokioExecutor::new()).build(https_or_http);
+        if let Some(failed) = after.iter().find(|e| *e.state.borrow() != Some(true)) {
+            self.last_error = Some(CheckError::Dependency(failed.name.clone()));
+            return false;
+        }
+        if blocked {
+            self.last_error = None;
+            self.generator.generate_waiting(vec![]).await;
+        }
+        true
+    }
 
-        let ToCheck::HttpOrHttpsUrl(ref url) = self.to_check else {
-            unreachable!()
-        };
+    /// Waits for the dependencies and then for the item itself
+    async fn run(&mut self) -> Option<Option<String>> {
+        if !self.wait_for_dependencies().await {
+            return None;
+        }
+        Some(self.wait_for_connection().await)
+    }
 
+    async fn wait_for_connection(&mut self) -> Option<String> {
+        let attempt_timeout = self
+            .options
+            .attempt_timeout
+            .unwrap_or_else(|| self.probe.attempt_timeout());
+        let interval = self.options.retry.unwrap_or_default().interval();
+        let stable = self.options.stable.unwrap_or(1);
+        let required_stable_for = self.options.stable_for.unwrap_or_default();
+
+        // consecutive successful attempts and the time of the first one
+        let mut streak = 0;
+        let mut streak_start = Instant::now();
         loop {
             self.generator.generate_tick().await;
-            let timeout = time::timeout(
-                Duration::from_millis(NO_RESPONSE_TIMEOUT),
-                client.get(url.clone()),
-            )
-            .await;
+            self.attempts += 1;
+            let timeout = time::timeout(attempt_timeout, self.probe.attempt()).await;
 
             match timeout {
-                Ok(Err(_)) => {
-                    time::sleep(Duration::from_millis(RETRY_TIMEOUT)).await;
+                Ok(Ok(details)) => {
+                    self.record(Ok(()));
+                    if streak == 0 {
+                        streak_start = Instant::now();
+                    }
+                    streak += 1;
+                    let stable_for = streak_start.elapsed();
+                    if stable > 1 || !required_stable_for.is_zero() {
+                        self.generator.generate_streak(streak, stable_for).await;
+                    }
+                    if streak >= stable && stable_for >= required_stable_for {
+                        return details;
+                    }
+                    self.backoff.reset();
+                    time::sleep(interval).await;
+                }
+                Ok(Err(err)) => {
+                    if streak > 0 {
+                        streak = 0;
+                        self.generator.generate_streak(0, Duration::ZERO).await;
+                    }
+                    self.record(Err(err));
+                    time::sleep(self.backoff.next_delay()).await;
                 }
-                Ok(Ok(resp)) => {
-                    if resp.status() == StatusCode::OK {
-                        break;
-                    } else {
-                        time::sleep(Duration::from_millis(RETRY_TIMEOUT)).await;
+                Err(_) => {
+                    if streak > 0 {
+                        streak = 0;
+                        self.generator.generate_streak(0, Duration::ZERO).await;
                     }
+                    self.record(Err(CheckError::Timeout(attempt_timeout)));
+                    time::sleep(self.backoff.next_delay()).await;
                 }
-                Err(_) => continue,
             }
         }
     }
 
-    #[cfg(not(feature = "http"))]
-    async fn wait_for_connection_http(&mut self) {
-        panic!("Not compiled with 'http' feature")
-    }
-
-    fn wait_future(mut self) -> Pin<Box<dyn Future<Output = Option<u64>>>> {
+    fn wait_future(mut self) -> WaitFuture {
         Box::pin(async move {
-            if let Some(timeout) = self.timeout {
-                let res = if let ToCheck::HostnameAndPort(..) = self.to_check {
-                    time::timeout(timeout, self.wait_for_connection_tcp()).await
-                } else {
-                    time::timeout(timeout, self.wait_for_connection_http()).await
-                };
-                if res.is_ok() {
-                    Some(self.generator.generate_success().await)
-                } else {
-                    self.generator.generate_error().await;
-                    None
-                }
+            let res = if let Some(timeout) = self.options.timeout {
+                time::timeout(timeout, self.run()).await.ok().flatten()
             } else {
-                if let ToCheck::HostnameAndPort(..) = self.to_check {
-                    self.wait_for_connection_tcp().await;
-                } else {
-                    self.wait_for_connection_http().await;
-                };
-                Some(self.generator.generate_success().await)
+                self.run().await
+            };
+            // the generator may measure the time since its last tick
+            let elapsed = self.instant.elapsed();
+            let (status, details) = if let Some(details) = res {
+                self.generator.generate_success(details.clone()).await;
+                (Status::Ready, details)
+            } else {
+                let reason = self.last_error.as_ref().map(CheckError::to_string);
+                self.generator.generate_error(reason).await;
+                (Status::Failed, None)
+            };
+            let last_change = self
+                .last_change
+                .map(|(_, at)| at.duration_since(self.instant))
+                .unwrap_or(elapsed);
+            TargetResult {
+                to_check: self.to_check,
+                status,
+                elapsed,
+                attempts: self.attempts,
+                first_error: self.first_error,
+                last_error: self.last_error,
+                last_change,
+                details,
             }
         })
     }
 }
 
+/// Describes consecutive successful attempts
+fn streak_message((streak, stable_for): (u32, Duration)) -> String {
+    format!(
+        "{streak} consecutive success{} in {:.3} seconds",
+        if streak == 1 { "" } else { "es" },
+        stable_for.as_secs_f32()
+    )
+}
+
 #[allow(dead_code)]
-pub trait Generator {
-    fn generate_tick(&mut self) -> Pin<Box<dyn Future<Output = ()>>>;
-    fn generate_error(&mut self) -> Pin<Box<dyn Future<Output = ()>>>;
-    fn generate_start(&mut self) -> Pin<Box<dyn Future<Output = ()>>>;
-    fn generate_success(&mut self) -> Pin<Box<dyn Future<Output = u64>>>;
+pub trait Generator: Send {
+    fn generate_tick(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+    fn generate_error(
+        &mut self,
+        reason: Option<String>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+    fn generate_start(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+    /// Reports the names of the items and groups which are not ready yet,
+    /// empty `on` means that the item is no longer blocked
+    fn generate_waiting(&mut self, on: Vec<String>) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+    /// Reports consecutive successful attempts of an item which is not stable yet,
+    /// zero `streak` means that the streak was interrupted
+    fn generate_streak(
+        &mut self,
+        streak: u32,
+        stable_for: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+    fn generate_success(
+        &mut self,
+        details: Option<String>,
+    ) -> Pin<Box<dyn Future<Output = u64> + Send>>;
 }
 
 pub struct SilentGenerator {
@@ -244,16 +529,32 @@ impl SilentGenerator {
 }
 
 impl Generator for SilentGenerator {
-    fn generate_tick(&mut self) -> Pin<Box<dyn Future<Output = ()>>> {
+    fn generate_tick(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         Box::pin(async {})
     }
-    fn generate_error(&mut self) -> Pin<Box<dyn Future<Output = ()>>> {
+    fn generate_error(
+        &mut self,
+        _reason: Option<String>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         Box::pin(async {})
     }
-    fn generate_start(&mut self) -> Pin<Box<dyn Future<Output = ()>>> {
+    fn generate_start(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         Box::pin(async {})
     }
-    fn generate_success(&mut self) -> Pin<Box<dyn Future<Output = u64>>> {
+    fn generate_waiting(&mut self, _on: Vec<String>) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(async {})
+    }
+    fn generate_streak(
+        &mut self,
+        _streak: u32,
+        _stable_for: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(async {})
+    }
+    fn generate_success(
+        &mut self,
+        _details: Option<String>,
+    ) -> Pin<Box<dyn Future<Output = u64> + Send>> {
         let instant = self.instant;
         Box::pin(async move { instant.elapsed().as_millis() as u64 })
     }
@@ -263,40 +564,77 @@ impl Generator for SilentGenerator {
 pub struct ProgressGenerator {
     to_check: ToCheck,
     instant: Instant,
+    streak: Option<(u32, Duration)>,
 }
 
 #[cfg(not(feature = "ui"))]
 impl Generator for ProgressGenerator {
-    fn generate_tick(&mut self) -> Pin<Box<dyn Future<Output = ()>>> {
+    fn generate_tick(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         Box::pin(async {})
     }
 
-    fn generate_start(&mut self) -> Pin<Box<dyn Future<Output = ()>>> {
+    fn generate_start(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         Box::pin(async {}) // TODO something more reasonable
     }
 
-    fn generate_error(&mut self) -> Pin<Box<dyn Future<Output = ()>>> {
+    fn generate_waiting(&mut self, on: Vec<String>) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let to_check = self.to_check.clone();
+        let instant = self.instant;
+        Box::pin(async move {
+            if !on.is_empty() {
+                println!(
+                    "'{}' is waiting on {} after {:.3} seconds",
+                    to_check,
+                    on.join(", "),
+                    instant.elapsed().as_secs_f32(),
+                )
+            }
+        })
+    }
+
+    fn generate_streak(
+        &mut self,
+        streak: u32,
+        stable_for: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.streak = (streak > 0).then_some((streak, stable_for));
+        Box::pin(async {})
+    }
+
+    fn generate_error(
+        &mut self,
+        reason: Option<String>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         let to_check = self.to_check.clone();
         let instant = self.instant;
 
         Box::pin(async move {
             println!(
-                "Failed to connect to '{}' in {:.3} seconds",
+                "Failed to connect to '{}' in {:.3} seconds{}",
                 to_check,
-                instant.elapsed().as_secs_f32()
+                instant.elapsed().as_secs_f32(),
+                reason.map(|e| format!(" ({e})")).unwrap_or_default(),
             )
         })
     }
 
-    fn generate_success(&mut self) -> Pin<Box<dyn Future<Output = u64>>> {
+    fn generate_success(
+        &mut self,
+        details: Option<String>,
+    ) -> Pin<Box<dyn Future<Output = u64> + Send>> {
         let to_check = self.to_check.clone();
         let instant = self.instant;
+        let details = match (details, self.streak.map(streak_message)) {
+            (Some(details), Some(streak)) => Some(format!("{details}, {streak}")),
+            (details, streak) => details.or(streak),
+        };
 
         Box::pin(async move {
             println!(
-                "Successfully connected to '{}' in {:.3} seconds",
+                "Successfully connected to '{}' in {:.3} seconds{}",
                 to_check,
-                instant.elapsed().as_secs_f32()
+                instant.elapsed().as_secs_f32(),
+                details.map(|e| format!(" ({e})")).unwrap_or_default(),
             );
             instant.elapsed().as_millis() as u64
         })
@@ -311,7 +649,7 @@ pub struct ProgressGenerator {
 
 #[cfg(feature = "ui")]
 impl Generator for ProgressGenerator {
-    fn generate_tick(&mut self) -> Pin<Box<dyn Future<Output = ()>>> {
+    fn generate_tick(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         let progress = self.progress.clone();
         let instant = self.instant;
         self.instant = Instant::now();
@@ -323,27 +661,309 @@ impl Generator for ProgressGenerator {
         })
     }
 
-    fn generate_start(&mut self) -> Pin<Box<dyn Future<Output = ()>>> {
+    fn generate_start(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         Box::pin(async {}) // TODO something more reasonable
     }
 
-    fn generate_error(&mut self) -> Pin<Box<dyn Future<Output = ()>>> {
+    fn generate_waiting(&mut self, on: Vec<String>) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let progress = self.progress.clone();
+        Box::pin(async move {
+            let message = if on.is_empty() {
+                " ".to_string()
+            } else {
+                format!("… waiting on {}", on.join(", "))
+            };
+            progress.lock().await.set_message(message);
+        })
+    }
+
+    fn generate_streak(
+        &mut self,
+        streak: u32,
+        stable_for: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let progress = self.progress.clone();
+        Box::pin(async move {
+            let message = if streak > 0 {
+                format!("… {}", streak_message((streak, stable_for)))
+            } else {
+                " ".to_string()
+            };
+            progress.lock().await.set_message(message);
+        })
+    }
+
+    fn generate_error(
+        &mut self,
+        reason: Option<String>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         let progress = self.progress.clone();
         Box::pin(async move {
             let unlocked = progress.lock().await;
-            unlocked.finish_with_message("✘");
+            unlocked.finish_with_message(match reason {
+                Some(reason) => format!("✘ {reason}"),
+                None => "✘".to_string(),
+            });
         })
     }
 
-    fn generate_success(&mut self) -> Pin<Box<dyn Future<Output = u64>>> {
+    fn generate_success(
+        &mut self,
+        details: Option<String>,
+    ) -> Pin<Box<dyn Future<Output = u64> + Send>> {
         let progress = self.progress.clone();
         let instant = self.instant;
         Box::pin(async move {
             let milis: u64 = instant.elapsed().as_millis() as u64;
             let unlocked = progress.lock().await;
-            unlocked.set_message("✔");
+            unlocked.set_message(match details {
+                Some(details) => format!("✔ {details}"),
+                None => "✔".to_string(),
+            });
             unlocked.abandon();
             milis
         })
     }
 }
+
+/// The item is no longer waited for when the generator is dropped before it is done
+#[cfg(feature = "ui")]
+impl Drop for ProgressGenerator {
+    fn drop(&mut self) {
+        if let Ok(progress) = self.progress.try_lock() {
+            if !progress.is_finished() {
+                progress.abandon_with_message("- cancelled");
+            }
+        }
+    }
+}
+
+/// Formats the time as RFC 3339 in UTC, e.g. `2025-07-13T08:30:00.000Z`
+#[cfg(feature = "json")]
+fn rfc3339(time: SystemTime) -> String {
+    let millis = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let (days, millis) = (millis / 86_400_000, millis % 86_400_000);
+    // civil from days algorithm, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000,
+    )
+}
+
+/// Record shared between [`JsonGenerator`] and [`report_json`]
+#[cfg(feature = "json")]
+pub type SharedRecord = Arc<std::sync::Mutex<Record>>;
+
+/// Result of an item collected by [`JsonGenerator`]
+#[cfg(feature = "json")]
+pub struct Record {
+    target: String,
+    /// one of pending, success, failure or cancelled
+    status: &'static str,
+    elapsed: Option<u64>,
+    attempts: u32,
+    last_error: Option<String>,
+    details: Option<String>,
+    started_at: SystemTime,
+    finished_at: Option<SystemTime>,
+}
+
+#[cfg(feature = "json")]
+impl Record {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "target": self.target,
+            "status": self.status,
+            "elapsed_ms": self.elapsed,
+            "attempts": self.attempts,
+            "last_error": self.last_error,
+            "details": self.details,
+            "started_at": rfc3339(self.started_at),
+            "finished_at": self.finished_at.map(rfc3339),
+        })
+    }
+
+    /// Single line event with the record
+    fn event(&self, event: &str) -> serde_json::Value {
+        let mut json = self.to_json();
+        json["event"] = event.into();
+        json["timestamp"] = rfc3339(SystemTime::now()).into();
+        json
+    }
+
+    /// Single line event with the target and extra fields
+    fn brief(&self, event: &str, fields: serde_json::Value) -> serde_json::Value {
+        let mut json = serde_json::json!({
+            "event": event,
+            "target": self.target,
+            "timestamp": rfc3339(SystemTime::now()),
+        });
+        if let (Some(json), serde_json::Value::Object(fields)) = (json.as_object_mut(), fields) {
+            json.extend(fields);
+        }
+        json
+    }
+}
+
+#[cfg(feature = "json")]
+pub struct JsonGenerator {
+    instant: Instant,
+    record: SharedRecord,
+    stream: bool,
+}
+
+#[cfg(feature = "json")]
+impl JsonGenerator {
+    pub fn new(to_check: &ToCheck, instant: Instant, stream: bool) -> Self {
+        let record = Record {
+            target: to_check.to_string(),
+            status: "pending",
+            elapsed: None,
+            attempts: 0,
+            last_error: None,
+            details: None,
+            started_at: SystemTime::now() - instant.elapsed(),
+            finished_at: None,
+        };
+        if stream {
+            println!("{}", record.brief("start", serde_json::json!({})));
+        }
+        Self {
+            instant,
+            record: Arc::new(std::sync::Mutex::new(record)),
+            stream,
+        }
+    }
+
+    fn finish(&self, status: &'static str, reason: Option<String>, details: Option<String>) -> u64 {
+        let elapsed = self.instant.elapsed().as_millis() as u64;
+        let mut record = self.record.lock().unwrap();
+        record.status = status;
+        record.elapsed = Some(elapsed);
+        record.last_error = reason;
+        record.details = details;
+        record.finished_at = Some(SystemTime::now());
+        if self.stream {
+            println!("{}", record.event(status));
+        }
+        elapsed
+    }
+}
+
+#[cfg(feature = "json")]
+impl Generator for JsonGenerator {
+    fn generate_tick(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.record.lock().unwrap().attempts += 1;
+        Box::pin(async {})
+    }
+
+    fn generate_start(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(async {})
+    }
+
+    fn generate_waiting(&mut self, on: Vec<String>) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        if self.stream {
+            let record = self.record.lock().unwrap();
+            println!(
+                "{}",
+                record.brief("waiting", serde_json::json!({ "on": on }))
+            );
+        }
+        Box::pin(async {})
+    }
+
+    fn generate_streak(
+        &mut self,
+        streak: u32,
+        stable_for: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        if self.stream {
+            let record = self.record.lock().unwrap();
+            let fields = serde_json::json!({
+                "streak": streak,
+                "stable_for_ms": stable_for.as_millis() as u64,
+            });
+            println!("{}", record.brief("streak", fields));
+        }
+        Box::pin(async {})
+    }
+
+    fn generate_error(
+        &mut self,
+        reason: Option<String>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.finish("failure", reason, None);
+        Box::pin(async {})
+    }
+
+    fn generate_success(
+        &mut self,
+        details: Option<String>,
+    ) -> Pin<Box<dyn Future<Output = u64> + Send>> {
+        let elapsed = self.finish("success", None, details);
+        Box::pin(async move { elapsed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Attempt, Probe, SilentGenerator, Wait};
+    use crate::{CheckError, Status, Target};
+    use std::{
+        collections::VecDeque,
+        future::Future,
+        pin::Pin,
+        time::{Duration, Instant},
+    };
+
+    /// Probe which replays the given outcomes and then keeps succeeding
+    struct Scripted(VecDeque<Attempt>);
+
+    impl Probe for Scripted {
+        fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+            let attempt = self.0.pop_front().unwrap_or(Ok(None));
+            Box::pin(async move { attempt })
+        }
+    }
+
+    #[tokio::test]
+    async fn failures_and_last_change() {
+        let target: Target = "localhost:1@stable=3,retry=fixed:20ms".parse().unwrap();
+        let instant = Instant::now();
+        let mut wait = Wait::new(
+            &target,
+            vec![],
+            Box::new(target.options.retry.unwrap().backoff()),
+            instant,
+            Box::new(SilentGenerator::new(instant)),
+        );
+        wait.probe = Box::new(Scripted(VecDeque::from([
+            Err(CheckError::Refused),
+            Err(CheckError::Status(503)),
+        ])));
+
+        let result = wait.wait_future().await;
+        assert_eq!(result.status, Status::Ready);
+        assert_eq!(result.attempts, 5);
+        assert_eq!(result.first_error, Some(CheckError::Refused));
+        assert_eq!(result.last_error, Some(CheckError::Status(503)));
+        assert_ne!(result.first_error, result.last_error);
+        assert!(result.last_change >= Duration::from_millis(40));
+        assert!(result.last_change < result.elapsed);
+    }
+}
@@ -0,0 +1,142 @@
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper::{header, Request, StatusCode};
+use serde_json::Value;
+use std::{env, fmt, future::Future, path::PathBuf, pin::Pin, str::FromStr};
+
+use super::{Attempt, Probe};
+use crate::CheckError;
+
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+/// Docker container which has to be healthy
+///
+/// it may be parsed from `docker://<container name or id>`
+///
+/// Docker Engine API is queried over unix socket from `DOCKER_HOST`
+/// (`unix:///var/run/docker.sock` by default).
+/// The container is ready when its healthcheck reports `healthy`
+/// or when it is `running` and no healthcheck is defined.
+///
+/// ```
+/// let check: wait_for_them::DockerCheck = "docker://my-project-db-1".parse().unwrap();
+/// assert_eq!(check.container, "my-project-db-1");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct DockerCheck {
+    pub container: String,
+}
+
+impl DockerCheck {
+    fn socket() -> Result<PathBuf, String> {
+        match env::var("DOCKER_HOST") {
+            Ok(host) if !host.is_empty() => host
+                .strip_prefix("unix://")
+                .map(PathBuf::from)
+                .ok_or_else(|| format!("DOCKER_HOST '{host}' is not a unix socket")),
+            _ => Ok(PathBuf::from(DEFAULT_SOCKET)),
+        }
+    }
+
+    #[cfg(unix)]
+    async fn inspect(&self) -> Result<(StatusCode, Bytes), String> {
+        let socket = Self::socket()?;
+        let stream = tokio::net::UnixStream::connect(&socket)
+            .await
+            .map_err(|err| format!("'{}': {err}", socket.display()))?;
+        let (mut sender, connection) =
+            hyper::client::conn::http1::handshake(hyper_util::rt::TokioIo::new(stream))
+                .await
+                .map_err(|err| err.to_string())?;
+        tokio::spawn(connection);
+
+        let request = Request::get(format!("/containers/{}/json", self.container))
+            .header(header::HOST, "docker")
+            .body(Empty::<Bytes>::new())
+            .map_err(|err| err.to_string())?;
+        let resp = sender
+            .send_request(request)
+            .await
+            .map_err(|err| err.to_string())?;
+        let status = resp.status();
+        let body = resp
+            .into_body()
+            .collect()
+            .await
+            .map_err(|err| err.to_string())?
+            .to_bytes();
+        Ok((status, body))
+    }
+
+    #[cfg(not(unix))]
+    async fn inspect(&self) -> Result<(StatusCode, Bytes), String> {
+        Err("only unix sockets are supported".to_string())
+    }
+}
+
+impl Probe for DockerCheck {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move {
+            let (status, body) = self.inspect().await.map_err(CheckError::Io)?;
+            if status == StatusCode::NOT_FOUND {
+                return Err("no such container".to_string().into());
+            }
+            if status != StatusCode::OK {
+                return Err(CheckError::Status(status.as_u16()));
+            }
+            let inspect: Value =
+                serde_json::from_slice(&body).map_err(|err| CheckError::Io(err.to_string()))?;
+            container_state(&inspect)
+        })
+    }
+}
+
+/// Evaluates `State` of the container inspect response
+fn container_state(inspect: &Value) -> Attempt {
+    let state = &inspect["State"];
+    let status = state["Status"].as_str().unwrap_or("unknown");
+    if status != "running" {
+        return Err(format!("container is {status}").into());
+    }
+
+    let health = &state["Health"];
+    match health["Status"].as_str() {
+        None | Some("none") => Ok(Some("running".to_string())),
+        Some("healthy") => Ok(Some("running, healthy".to_string())),
+        Some(health_status) => {
+            // output of the latest healthcheck run
+            let output = health["Log"]
+                .as_array()
+                .and_then(|e| e.last())
+                .and_then(|e| e["Output"].as_str())
+                .and_then(|e| e.lines().rev().map(str::trim).find(|e| !e.is_empty()));
+            match output {
+                Some(output) => Err(format!("container is {health_status}: {output}").into()),
+                None => Err(format!("container is {health_status}").into()),
+            }
+        }
+    }
+}
+
+impl FromStr for DockerCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let container = s.strip_prefix("docker://").unwrap_or_default();
+        let valid = container
+            .chars()
+            .all(|e| e.is_ascii_alphanumeric() || "_.-".contains(e));
+        if container.is_empty() || !valid {
+            return Err(format!("'{s}' doesn't match docker://<container> pattern"));
+        }
+        Ok(Self {
+            container: container.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for DockerCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "docker://{}", self.container)
+    }
+}
@@ -0,0 +1,114 @@
+use std::{fmt, future::Future, io, pin::Pin, process::Stdio, str::FromStr, time::Duration};
+use tokio::process::Command;
+
+use super::{Attempt, Probe};
+use crate::CheckError;
+
+/// Time limit of a single command run
+const EXEC_TIMEOUT: u64 = 10_000_u64;
+
+/// Shell command which has to exit with status code `0`
+///
+/// it may be parsed from `exec:<command>`
+///
+/// ```
+/// let check: wait_for_them::ExecCheck = "exec:pg_isready -h db".parse().unwrap();
+/// assert_eq!(check.command, "pg_isready -h db");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExecCheck {
+    pub command: String,
+}
+
+impl Probe for ExecCheck {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move {
+            let (status, stderr) = run(&self.command).await.map_err(CheckError::from)?;
+            if status.success() {
+                return Ok(None);
+            }
+
+            let stderr: Vec<&str> = stderr
+                .lines()
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+                .collect();
+            if stderr.is_empty() {
+                Err(status.to_string().into())
+            } else {
+                Err(format!("{status}: {}", stderr.join("; ")).into())
+            }
+        })
+    }
+
+    fn attempt_timeout(&self) -> Duration {
+        Duration::from_millis(EXEC_TIMEOUT)
+    }
+}
+
+/// Runs the command using system shell and returns its exit status with the captured stderr
+///
+/// The command and all processes it started are killed when the returned future is dropped.
+async fn run(command: &str) -> Result<(std::process::ExitStatus, String), io::Error> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    shell.process_group(0);
+
+    let child = shell.spawn()?;
+    let mut guard = ProcessGroupGuard(child.id());
+    let output = child.wait_with_output().await?;
+    guard.0 = None;
+
+    Ok((
+        output.status,
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    ))
+}
+
+/// Kills the whole process group of an unfinished command
+struct ProcessGroupGuard(Option<u32>);
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0 {
+            // SAFETY: the group was created for the command and it is still running
+            unsafe {
+                libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+            }
+        }
+    }
+}
+
+impl FromStr for ExecCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("exec:").map(str::trim) {
+            Some(command) if !command.is_empty() => Ok(Self {
+                command: command.to_string(),
+            }),
+            _ => Err(format!("'{s}' doesn't match exec:<command> pattern")),
+        }
+    }
+}
+
+impl fmt::Display for ExecCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "exec:{}", self.command)
+    }
+}
@@ -0,0 +1,246 @@
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{header, Method, Request, StatusCode};
+use hyper_tls::HttpsConnector;
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::TokioExecutor,
+};
+use std::{fmt, future::Future, pin::Pin, str::FromStr};
+
+use super::{Attempt, Probe};
+use crate::{parse_host_and_port, CheckError};
+
+const HEALTH_CHECK_PATH: &str = "/grpc.health.v1.Health/Check";
+/// `SERVING` value of `HealthCheckResponse.ServingStatus`
+const SERVING: u64 = 1;
+
+/// gRPC service implementing the standard health checking protocol
+///
+/// it may be parsed from `grpc://<hostname>:<port>[/<service>]`
+/// or `grpcs://<hostname>:<port>[/<service>]` for TLS connections.
+/// Empty service name means the overall health of the server.
+///
+/// ```
+/// let check: wait_for_them::GrpcCheck = "grpcs://localhost:50051/my.Service".parse().unwrap();
+/// assert_eq!(check.service, "my.Service");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct GrpcCheck {
+    pub host: String,
+    pub port: u16,
+    pub service: String,
+    pub secure: bool,
+}
+
+pub(crate) struct GrpcProbe {
+    /// the TLS backend may fail to initialize, which is reported by each attempt
+    client: Result<Client<HttpsConnector<HttpConnector>, Full<Bytes>>, CheckError>,
+    uri: hyper::Uri,
+    service: String,
+}
+
+impl GrpcProbe {
+    pub fn new(check: &GrpcCheck) -> Self {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        let client = native_tls::TlsConnector::builder()
+            .request_alpns(&["h2"])
+            .build()
+            .map(|tls| {
+                Client::builder(TokioExecutor::new())
+                    .http2_only(true)
+                    .build(HttpsConnector::from((http, tls.into())))
+            })
+            .map_err(|err| CheckError::Tls(err.to_string()));
+
+        let scheme = if check.secure { "https" } else { "http" };
+        let uri = format!(
+            "{scheme}://{}:{}{HEALTH_CHECK_PATH}",
+            check.host, check.port
+        )
+        .parse()
+        .expect("hostname and port were already validated");
+
+        Self {
+            client,
+            uri,
+            service: check.service.clone(),
+        }
+    }
+}
+
+impl Probe for GrpcProbe {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move {
+            let request = Request::builder()
+                .method(Method::POST)
+                .uri(self.uri.clone())
+                .header(header::CONTENT_TYPE, "application/grpc")
+                .header(header::TE, "trailers")
+                .body(Full::new(encode_request(&self.service)))
+                .map_err(|err| CheckError::Io(err.to_string()))?;
+
+            let resp = self
+                .client
+                .as_ref()
+                .map_err(Clone::clone)?
+                .request(request)
+                .await
+                .map_err(|err| CheckError::from_chain(&err))?;
+            if resp.status() != StatusCode::OK {
+                return Err(CheckError::Status(resp.status().as_u16()));
+            }
+            // trailers-only responses carry the status in headers
+            check_grpc_status(resp.headers())?;
+
+            let collected = resp
+                .into_body()
+                .collect()
+                .await
+                .map_err(|err| CheckError::Io(err.to_string()))?;
+            if let Some(trailers) = collected.trailers() {
+                check_grpc_status(trailers)?;
+            }
+
+            match decode_response(&collected.to_bytes())? {
+                SERVING => Ok(None),
+                status => Err(format!("status {}", serving_status_name(status)).into()),
+            }
+        })
+    }
+}
+
+fn check_grpc_status(headers: &hyper::HeaderMap) -> Result<(), String> {
+    let Some(status) = headers.get("grpc-status") else {
+        return Ok(());
+    };
+    if status == "0" {
+        return Ok(());
+    }
+    let message = headers
+        .get("grpc-message")
+        .and_then(|e| e.to_str().ok())
+        .unwrap_or_default();
+    Err(format!(
+        "grpc-status {} {message}",
+        status.to_str().unwrap_or_default()
+    ))
+}
+
+/// Encodes length-prefixed `HealthCheckRequest` message
+fn encode_request(service: &str) -> Bytes {
+    let mut message = vec![];
+    if !service.is_empty() {
+        // field 1, wire type 2 (length-delimited)
+        message.push(0x0a);
+        encode_varint(service.len() as u64, &mut message);
+        message.extend_from_slice(service.as_bytes());
+    }
+
+    let mut frame = vec![0];
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend(message);
+    frame.into()
+}
+
+fn encode_varint(mut value: u64, output: &mut Vec<u8>) {
+    while value >= 0x80 {
+        output.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+fn decode_varint(input: &mut &[u8]) -> Result<u64, String> {
+    let mut value = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let [byte, rest @ ..] = *input else {
+            break;
+        };
+        *input = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("malformed health check response".to_string())
+}
+
+/// Decodes length-prefixed `HealthCheckResponse` and returns its status
+fn decode_response(frame: &[u8]) -> Result<u64, String> {
+    let malformed = || "malformed health check response".to_string();
+
+    let [0, a, b, c, d, rest @ ..] = frame else {
+        return Err(malformed());
+    };
+    let mut message = rest
+        .get(..u32::from_be_bytes([*a, *b, *c, *d]) as usize)
+        .ok_or_else(malformed)?;
+
+    // missing field means default value - UNKNOWN
+    let mut status = 0;
+    while !message.is_empty() {
+        let key = decode_varint(&mut message)?;
+        match key & 0x07 {
+            0 => {
+                let value = decode_varint(&mut message)?;
+                if key >> 3 == 1 {
+                    status = value;
+                }
+            }
+            2 => {
+                let length = decode_varint(&mut message)? as usize;
+                message = message.get(length..).ok_or_else(malformed)?;
+            }
+            _ => return Err(malformed()),
+        }
+    }
+    Ok(status)
+}
+
+fn serving_status_name(status: u64) -> String {
+    match status {
+        0 => "UNKNOWN".to_string(),
+        1 => "SERVING".to_string(),
+        2 => "NOT_SERVING".to_string(),
+        3 => "SERVICE_UNKNOWN".to_string(),
+        other => other.to_string(),
+    }
+}
+
+impl FromStr for GrpcCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (secure, rest) = if let Some(rest) = s.strip_prefix("grpc://") {
+            (false, rest)
+        } else if let Some(rest) = s.strip_prefix("grpcs://") {
+            (true, rest)
+        } else {
+            return Err(format!(
+                "'{s}' doesn't match grpc(s)://<hostname>:<port>[/<service>] pattern"
+            ));
+        };
+        let (host_and_port, service) = rest.split_once('/').unwrap_or((rest, ""));
+        let (host, port) = parse_host_and_port(host_and_port)?;
+
+        Ok(Self {
+            host,
+            port,
+            service: service.to_string(),
+            secure,
+        })
+    }
+}
+
+impl fmt::Display for GrpcCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scheme = if self.secure { "grpcs" } else { "grpc" };
+        write!(f, "{scheme}://{}:{}", self.host, self.port)?;
+        if !self.service.is_empty() {
+            write!(f, "/{}", self.service)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper::{header, Request, StatusCode};
+use hyper_tls::HttpsConnector;
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::TokioExecutor,
+};
+use serde_json::Value;
+use std::{env, fmt, future::Future, pin::Pin, str::FromStr};
+
+use super::{Attempt, Probe};
+use crate::CheckError;
+
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+const API_ENV: &str = "WAIT_FOR_THEM_K8S_API";
+const TOKEN_FILE_ENV: &str = "WAIT_FOR_THEM_K8S_TOKEN_FILE";
+
+type ApiClient = Client<HttpsConnector<HttpConnector>, Empty<Bytes>>;
+
+/// Kind of the kubernetes resource
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum K8sKind {
+    /// all replicas are ready
+    Deployment,
+    /// all replicas are ready
+    StatefulSet,
+    /// `Ready` condition is true
+    Pod,
+    /// the job completed
+    Job,
+}
+
+/// Kubernetes resource which has to be ready
+///
+/// it may be parsed from `k8s://<namespace>/<kind>/<name>` where kind is one of
+/// `deployment`, `statefulset`, `pod` or `job`.
+///
+/// The API server is queried using the in-cluster service account token and CA.
+/// `WAIT_FOR_THEM_K8S_API` environment variable may override the API server url
+/// (e.g. `http://127.0.0.1:8001` for `kubectl proxy`). The service account token
+/// is not sent to the overridden url, a token may be read from the file named by
+/// `WAIT_FOR_THEM_K8S_TOKEN_FILE` instead, which requires an `https://` url.
+///
+/// ```
+/// let check: wait_for_them::K8sCheck = "k8s://default/deployment/web".parse().unwrap();
+/// assert_eq!(check.kind, wait_for_them::K8sKind::Deployment);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct K8sCheck {
+    pub namespace: String,
+    pub kind: K8sKind,
+    pub name: String,
+}
+
+impl K8sCheck {
+    fn path(&self) -> String {
+        let (prefix, resource) = match self.kind {
+            K8sKind::Deployment => ("apis/apps/v1", "deployments"),
+            K8sKind::StatefulSet => ("apis/apps/v1", "statefulsets"),
+            K8sKind::Pod => ("api/v1", "pods"),
+            K8sKind::Job => ("apis/batch/v1", "jobs"),
+        };
+        format!(
+            "/{prefix}/namespaces/{}/{resource}/{}",
+            self.namespace, self.name
+        )
+    }
+}
+
+/// Client of the API server
+struct Api {
+    client: ApiClient,
+    url: String,
+    /// file with the bearer token, its absence is an error unless it is the service account one
+    token: Option<(String, bool)>,
+}
+
+pub(crate) struct K8sProbe {
+    check: K8sCheck,
+    api: Option<Api>,
+}
+
+impl K8sProbe {
+    pub fn new(check: &K8sCheck) -> Self {
+        Self {
+            check: check.clone(),
+            api: None,
+        }
+    }
+
+    /// Creates a client of the overridden API server or of the in-cluster one trusting the cluster CA
+    async fn api() -> Result<Api, String> {
+        let mut tls = native_tls::TlsConnector::builder();
+        let (url, token) = match env::var(API_ENV) {
+            Ok(url) if !url.is_empty() => {
+                let token = env::var(TOKEN_FILE_ENV).ok().filter(|e| !e.is_empty());
+                if token.is_some() && !url.starts_with("https://") {
+                    return Err(format!(
+                        "{TOKEN_FILE_ENV} requires {API_ENV} to be an https:// url"
+                    ));
+                }
+                (
+                    url.trim_end_matches('/').to_string(),
+                    token.map(|e| (e, true)),
+                )
+            }
+            _ => {
+                let host = env::var("KUBERNETES_SERVICE_HOST")
+                    .map_err(|_| "KUBERNETES_SERVICE_HOST is not set".to_string())?;
+                let port = env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".into());
+                let ca = format!("{SERVICE_ACCOUNT_DIR}/ca.crt");
+                let pem = tokio::fs::read(&ca)
+                    .await
+                    .map_err(|err| format!("'{ca}': {err}"))?;
+                tls.add_root_certificate(
+                    native_tls::Certificate::from_pem(&pem).map_err(|err| err.to_string())?,
+                );
+                let url = if host.contains(':') {
+                    format!("https://[{host}]:{port}")
+                } else {
+                    format!("https://{host}:{port}")
+                };
+                (url, Some((format!("{SERVICE_ACCOUNT_DIR}/token"), false)))
+            }
+        };
+
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        let tls = tls.build().map_err(|err| err.to_string())?;
+        let client =
+            Client::builder(TokioExecutor::new()).build(HttpsConnector::from((http, tls.into())));
+        Ok(Api { client, url, token })
+    }
+}
+
+impl Probe for K8sProbe {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move {
+            if self.api.is_none() {
+                self.api = Some(Self::api().await.map_err(CheckError::Io)?);
+            }
+            let Api { client, url, token } = self.api.as_ref().unwrap();
+
+            let mut request = Request::get(format!("{url}{}", self.check.path()));
+            // the token is rotated so it is read on each attempt
+            if let Some((path, required)) = token {
+                match tokio::fs::read_to_string(path).await {
+                    Ok(token) => {
+                        request = request
+                            .header(header::AUTHORIZATION, format!("Bearer {}", token.trim()))
+                    }
+                    Err(err) if *required => return Err(format!("'{path}': {err}").into()),
+                    Err(_) => {}
+                }
+            }
+            let request = request
+                .body(Empty::new())
+                .map_err(|err| CheckError::Io(err.to_string()))?;
+
+            let resp = client
+                .request(request)
+                .await
+                .map_err(|err| CheckError::from_chain(&err))?;
+            let status = resp.status();
+            if status == StatusCode::NOT_FOUND {
+                return Err(format!("{} not found", self.check.kind).into());
+            }
+            if status != StatusCode::OK {
+                return Err(CheckError::Status(status.as_u16()));
+            }
+            let body = resp
+                .into_body()
+                .collect()
+                .await
+                .map_err(|err| CheckError::Io(err.to_string()))?
+                .to_bytes();
+            let resource: Value =
+                serde_json::from_slice(&body).map_err(|err| CheckError::Io(err.to_string()))?;
+            Ok(resource_state(self.check.kind, &resource)?)
+        })
+    }
+}
+
+fn condition<'a>(resource: &'a Value, kind: &str) -> Option<&'a Value> {
+    resource["status"]["conditions"]
+        .as_array()?
+        .iter()
+        .find(|e| e["type"] == kind)
+}
+
+/// Evaluates the readiness of the resource
+fn resource_state(kind: K8sKind, resource: &Value) -> Result<Option<String>, String> {
+    match kind {
+        K8sKind::Deployment | K8sKind::StatefulSet => {
+            let desired = resource["spec"]["replicas"].as_u64().unwrap_or(1);
+            let ready = resource["status"]["readyReplicas"].as_u64().unwrap_or(0);
+            let generation = resource["metadata"]["generation"].as_u64().unwrap_or(0);
+            let observed = resource["status"]["observedGeneration"]
+                .as_u64()
+                .unwrap_or(0);
+            if observed < generation {
+                Err("latest generation is not observed yet".to_string())
+            } else if ready < desired {
+                Err(format!("{ready}/{desired} replicas ready"))
+            } else {
+                Ok(Some(format!("{ready}/{desired} replicas ready")))
+            }
+        }
+        K8sKind::Pod => {
+            let phase = resource["status"]["phase"].as_str().unwrap_or("Unknown");
+            match condition(resource, "Ready") {
+                Some(ready) if ready["status"] == "True" => Ok(Some(format!("{phase}, ready"))),
+                _ => Err(format!("pod is {phase}, not ready")),
+            }
+        }
+        K8sKind::Job => {
+            if let Some(failed) = condition(resource, "Failed").filter(|e| e["status"] == "True") {
+                let message = failed["message"]
+                    .as_str()
+                    .or(failed["reason"].as_str())
+                    .unwrap_or_default();
+                return Err(format!("job failed: {message}"));
+            }
+            let completions = resource["spec"]["completions"].as_u64().unwrap_or(1);
+            let succeeded = resource["status"]["succeeded"].as_u64().unwrap_or(0);
+            match condition(resource, "Complete") {
+                Some(complete) if complete["status"] == "True" => {
+                    Ok(Some(format!("{succeeded}/{completions} completions")))
+                }
+                _ => Err(format!("{succeeded}/{completions} completions")),
+            }
+        }
+    }
+}
+
+impl FromStr for K8sKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "deployment" | "deploy" => Ok(Self::Deployment),
+            "statefulset" | "sts" => Ok(Self::StatefulSet),
+            "pod" | "po" => Ok(Self::Pod),
+            "job" => Ok(Self::Job),
+            _ => Err(format!(
+                "'{s}' is not one of deployment, statefulset, pod or job"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for K8sKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Deployment => "deployment",
+            Self::StatefulSet => "statefulset",
+            Self::Pod => "pod",
+            Self::Job => "job",
+        }
+        .fmt(f)
+    }
+}
+
+impl FromStr for K8sCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s
+            .strip_prefix("k8s://")
+            .map(|e| e.split('/').collect())
+            .unwrap_or_default();
+        let [namespace, kind, name] = parts.as_slice() else {
+            return Err(format!(
+                "'{s}' doesn't match k8s://<namespace>/<kind>/<name> pattern"
+            ));
+        };
+        for part in [namespace, name] {
+            let valid = part
+                .chars()
+                .all(|e| e.is_ascii_lowercase() || e.is_ascii_digit() || "-.".contains(e));
+            if part.is_empty() || !valid {
+                return Err(format!("'{s}', '{part}' is not a valid kubernetes name"));
+            }
+        }
+
+        Ok(Self {
+            namespace: namespace.to_string(),
+            kind: kind
+                .parse()
+                .map_err(|err| format!("'{s}', kind error: {err}"))?,
+            name: name.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for K8sCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "k8s://{}/{}/{}", self.namespace, self.kind, self.name)
+    }
+}
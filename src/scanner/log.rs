@@ -0,0 +1,283 @@
+use regex::Regex;
+use std::{
+    fmt,
+    future::Future,
+    io::{ErrorKind, SeekFrom},
+    path::PathBuf,
+    pin::Pin,
+    str::FromStr,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
+
+use super::{Attempt, Probe};
+use crate::{split_query, CheckError};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Longer lines are split, so that a file without line breaks doesn't exhaust the memory
+const MAX_LINE_LENGTH: usize = 1024 * 1024;
+/// Number of the last read bytes kept to detect a truncated file which grew again
+const TAIL_SIZE: usize = 64;
+
+/// Log file which has to contain a matching line
+///
+/// it may be parsed from `log://<path>?match=<regex>[&from=start]`
+/// e.g. `log:///var/log/app.log?match=started`.
+///
+/// Only the lines appended after the waiting started are matched
+/// unless `from=start` is set. Rotated (re-created) and truncated files
+/// are read again from the beginning. Lines longer than 1 MiB are split.
+///
+/// ```
+/// let check: wait_for_them::LogCheck = "log:///var/log/app.log?match=Server%20started"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(check.path.to_str(), Some("/var/log/app.log"));
+/// assert!(!check.from_start);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct LogCheck {
+    pub path: PathBuf,
+    pub pattern: String,
+    pub from_start: bool,
+}
+
+/// Identifies the file so that rotation can be detected
+#[cfg(unix)]
+fn file_id(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+struct OpenLog {
+    file: File,
+    id: Option<(u64, u64)>,
+    position: u64,
+    /// last bytes before the position
+    tail: Vec<u8>,
+}
+
+impl OpenLog {
+    fn push_tail(&mut self, data: &[u8]) {
+        self.tail.extend_from_slice(data);
+        let excess = self.tail.len().saturating_sub(TAIL_SIZE);
+        self.tail.drain(..excess);
+    }
+
+    /// Checks whether the bytes before the position differ from the ones read,
+    /// i.e. the file was truncated and it grew past the position again
+    async fn tail_changed(&mut self) -> std::io::Result<bool> {
+        if self.tail.is_empty() {
+            return Ok(false);
+        }
+        self.file
+            .seek(SeekFrom::Start(self.position - self.tail.len() as u64))
+            .await?;
+        let mut current = vec![0; self.tail.len()];
+        match self.file.read_exact(&mut current).await {
+            Ok(_) => Ok(current != self.tail),
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => Ok(true),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+pub(crate) struct LogProbe {
+    path: PathBuf,
+    regex: Regex,
+    /// file was not opened yet, so its current content is skipped
+    skip_existing: bool,
+    log: Option<OpenLog>,
+    /// incomplete last line
+    pending: Vec<u8>,
+}
+
+impl LogProbe {
+    pub fn new(check: &LogCheck) -> Self {
+        Self {
+            path: check.path.clone(),
+            // validated while parsing
+            regex: Regex::new(&check.pattern).unwrap(),
+            skip_existing: !check.from_start,
+            log: None,
+            pending: vec![],
+        }
+    }
+
+    /// Reads the rest of the open file and returns the first matching line
+    async fn read_lines(&mut self) -> Result<Option<String>, String> {
+        let Some(log) = self.log.as_mut() else {
+            return Ok(None);
+        };
+        let mut chunk = vec![0; CHUNK_SIZE];
+        loop {
+            let read = log
+                .file
+                .read(&mut chunk)
+                .await
+                .map_err(|err| format!("'{}': {err}", self.path.display()))?;
+            if read == 0 {
+                return Ok(None);
+            }
+            log.position += read as u64;
+            log.push_tail(&chunk[..read]);
+            self.pending.extend_from_slice(&chunk[..read]);
+
+            let end = match self.pending.iter().rposition(|e| *e == b'\n') {
+                Some(end) => end,
+                None if self.pending.len() > MAX_LINE_LENGTH => self.pending.len() - 1,
+                None => continue,
+            };
+            let lines: Vec<u8> = self.pending.drain(..=end).collect();
+            for line in lines.split(|e| *e == b'\n') {
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                let line = String::from_utf8_lossy(line);
+                if self.regex.is_match(&line) {
+                    return Ok(Some(line.trim().to_string()));
+                }
+            }
+        }
+    }
+
+    /// Reads the open file from the beginning again when it was truncated
+    async fn rewind_truncated(&mut self) -> Result<(), String> {
+        let Some(log) = self.log.as_mut() else {
+            return Ok(());
+        };
+        let error = |err: std::io::Error| format!("'{}': {err}", self.path.display());
+        let len = log.file.metadata().await.map_err(error)?.len();
+        if len < log.position || log.tail_changed().await.map_err(error)? {
+            log.file.seek(SeekFrom::Start(0)).await.map_err(error)?;
+            log.position = 0;
+            log.tail.clear();
+            self.pending.clear();
+        }
+        Ok(())
+    }
+
+    /// Opens the file again when it was rotated
+    async fn reopen(&mut self) -> Result<(), String> {
+        let metadata = match tokio::fs::metadata(&self.path).await {
+            Ok(metadata) => metadata,
+            // rotation in progress or the file wasn't created yet
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                self.skip_existing = false;
+                return Ok(());
+            }
+            Err(err) => return Err(format!("'{}': {err}", self.path.display())),
+        };
+
+        let id = file_id(&metadata);
+        if self.log.as_ref().is_some_and(|e| e.id == id) {
+            return Ok(());
+        }
+
+        let file = File::open(&self.path)
+            .await
+            .map_err(|err| format!("'{}': {err}", self.path.display()))?;
+        let mut log = OpenLog {
+            file,
+            id,
+            position: 0,
+            tail: vec![],
+        };
+        if self.skip_existing {
+            let end = log
+                .file
+                .seek(SeekFrom::End(0))
+                .await
+                .map_err(|err| format!("'{}': {err}", self.path.display()))?;
+            let mut tail = vec![0; end.min(TAIL_SIZE as u64) as usize];
+            log.file
+                .seek(SeekFrom::Start(end - tail.len() as u64))
+                .await
+                .map_err(|err| format!("'{}': {err}", self.path.display()))?;
+            log.file
+                .read_exact(&mut tail)
+                .await
+                .map_err(|err| format!("'{}': {err}", self.path.display()))?;
+            log.position = end;
+            log.tail = tail;
+        }
+        self.skip_existing = false;
+        self.pending.clear();
+        self.log = Some(log);
+        Ok(())
+    }
+}
+
+impl Probe for LogProbe {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move {
+            self.rewind_truncated().await.map_err(CheckError::Io)?;
+            // lines written to the previous file before the rotation are read first
+            if let Some(line) = self.read_lines().await.map_err(CheckError::Io)? {
+                return Ok(Some(line));
+            }
+            self.reopen().await.map_err(CheckError::Io)?;
+            if self.log.is_none() {
+                return Err("file doesn't exist".to_string().into());
+            }
+            match self.read_lines().await.map_err(CheckError::Io)? {
+                Some(line) => Ok(Some(line)),
+                None => Err("no matching line".to_string().into()),
+            }
+        })
+    }
+}
+
+impl FromStr for LogCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, query) = split_query(s)?;
+        let path = path.strip_prefix("log://").unwrap_or_default();
+        if path.is_empty() {
+            return Err(format!(
+                "'{s}' doesn't match log://<path>?match=<regex> pattern"
+            ));
+        }
+
+        let mut pattern = None;
+        let mut from_start = false;
+        for (key, value) in query {
+            match key.as_str() {
+                "match" => {
+                    Regex::new(&value).map_err(|err| format!("'{s}', match error: {err}"))?;
+                    pattern = Some(value);
+                }
+                "from" => {
+                    from_start = match value.as_str() {
+                        "start" => true,
+                        "end" => false,
+                        _ => return Err(format!("'{s}', from has to be start or end")),
+                    }
+                }
+                _ => return Err(format!("'{s}', unknown option '{key}'")),
+            }
+        }
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            pattern: pattern.ok_or_else(|| format!("'{s}', match option is missing"))?,
+            from_start,
+        })
+    }
+}
+
+impl fmt::Display for LogCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "log://{}?match={}", self.path.display(), self.pattern)?;
+        if self.from_start {
+            write!(f, "&from=start")?;
+        }
+        Ok(())
+    }
+}
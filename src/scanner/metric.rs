@@ -0,0 +1,323 @@
+use http_body_util::BodyExt;
+use hyper::{StatusCode, Uri};
+use std::{fmt, future::Future, pin::Pin, str::FromStr};
+
+use super::{Attempt, HttpProbe, Probe};
+use crate::{percent_encode, split_query, CheckError};
+
+type Labels = Vec<(String, String)>;
+
+/// Comparison of the metric value with the threshold
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum MetricOp {
+    #[default]
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl MetricOp {
+    fn compare(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Self::Eq => value == threshold,
+            Self::Ne => value != threshold,
+            Self::Lt => value < threshold,
+            Self::Le => value <= threshold,
+            Self::Gt => value > threshold,
+            Self::Ge => value >= threshold,
+        }
+    }
+}
+
+/// Prometheus metric which has to reach a threshold
+///
+/// it may be parsed from `metric+http://<url>?name=<metric>&value=<threshold>[&op=<op>]`
+/// (or `metric+https://`) where the options are:
+///
+/// * `name` - metric name with optional label selector, e.g. `lag{topic="orders"}`
+/// * `value` - threshold the metric is compared with
+/// * `op` - one of `eq` (default), `ne`, `lt`, `le`, `gt` or `ge`
+///
+/// Other query parameters are kept in the url of the endpoint.
+/// The endpoint is expected to return Prometheus text exposition format.
+/// All samples matching the selector have to satisfy the comparison
+/// and at least one sample has to be present.
+///
+/// ```
+/// let check: wait_for_them::MetricCheck =
+///     "metric+http://localhost:9090/metrics?name=lag%7Btopic%3D%22orders%22%7D&op=lt&value=100"
+///         .parse()
+///         .unwrap();
+/// assert_eq!(check.name, "lag");
+/// assert_eq!(check.labels, vec![("topic".to_string(), "orders".to_string())]);
+/// assert_eq!(check.op, wait_for_them::MetricOp::Lt);
+///
+/// let item = "metric+http://localhost:9090/metrics?format=prometheus&name=up%7Bjob%3D%22a%26b%22%7D&value=1";
+/// let check: wait_for_them::MetricCheck = item.parse().unwrap();
+/// assert_eq!(check.url, "http://localhost:9090/metrics?format=prometheus");
+/// assert_eq!(check.labels, vec![("job".to_string(), "a&b".to_string())]);
+/// assert_eq!(check.to_string().parse::<wait_for_them::MetricCheck>(), Ok(check));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct MetricCheck {
+    pub url: Uri,
+    pub name: String,
+    pub labels: Vec<(String, String)>,
+    pub op: MetricOp,
+    pub value: f64,
+}
+
+impl MetricCheck {
+    fn matches(&self, name: &str, labels: &[(String, String)]) -> bool {
+        name == self.name
+            && self
+                .labels
+                .iter()
+                .all(|selector| labels.iter().any(|label| label == selector))
+    }
+
+    /// Compares all samples matching the selector
+    fn evaluate(&self, exposition: &str) -> Attempt {
+        let mut found = None;
+        for line in exposition.lines() {
+            let Some((name, labels, value)) = parse_sample(line) else {
+                continue;
+            };
+            if !self.matches(name, &labels) {
+                continue;
+            }
+            let series = series(name, &labels);
+            if !self.op.compare(value, self.value) {
+                return Err(
+                    format!("{series} is {value}, expected {} {}", self.op, self.value).into(),
+                );
+            }
+            found.get_or_insert(format!("{series} is {value}"));
+        }
+        match found {
+            Some(details) => Ok(Some(details)),
+            None => Err(format!("{} not found", series(&self.name, &self.labels)).into()),
+        }
+    }
+}
+
+fn series(name: &str, labels: &[(String, String)]) -> String {
+    if labels.is_empty() {
+        return name.to_string();
+    }
+    let labels = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{value}\""))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{name}{{{labels}}}")
+}
+
+/// Parses `{label="value",...}` and returns the labels and the remaining input
+///
+/// Values of the label selector don't have to be quoted.
+fn parse_labels(input: &str) -> Option<(Labels, &str)> {
+    let mut rest = input.strip_prefix('{')?;
+    let mut labels = vec![];
+    loop {
+        rest = rest.trim_start_matches(|e: char| e == ',' || e.is_whitespace());
+        if let Some(remaining) = rest.strip_prefix('}') {
+            return Some((labels, remaining));
+        }
+        let (key, remaining) = rest.split_once('=')?;
+        let key = key.trim();
+        if key.is_empty() {
+            return None;
+        }
+        let remaining = remaining.trim_start();
+        let (value, remaining) = if let Some(quoted) = remaining.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let end = loop {
+                match chars.next()? {
+                    (idx, '"') => break idx,
+                    (_, '\\') => match chars.next()?.1 {
+                        'n' => value.push('\n'),
+                        other => value.push(other),
+                    },
+                    (_, other) => value.push(other),
+                }
+            };
+            (value, &quoted[end + 1..])
+        } else {
+            let end = remaining.find([',', '}'])?;
+            (remaining[..end].trim().to_string(), &remaining[end..])
+        };
+        labels.push((key.to_string(), value));
+        rest = remaining;
+    }
+}
+
+/// Parses a sample line `name{labels} value [timestamp]` of the exposition format
+fn parse_sample(line: &str) -> Option<(&str, Labels, f64)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line
+        .find(|e: char| e == '{' || e.is_whitespace())
+        .unwrap_or(line.len());
+    let (name, rest) = line.split_at(end);
+    let (labels, rest) = if rest.starts_with('{') {
+        parse_labels(rest)?
+    } else {
+        (vec![], rest)
+    };
+    let value = rest.split_whitespace().next()?.parse().ok()?;
+    Some((name, labels, value))
+}
+
+pub(crate) struct MetricProbe {
+    http: HttpProbe,
+    check: MetricCheck,
+}
+
+impl MetricProbe {
+    pub fn new(check: &MetricCheck) -> Self {
+        Self {
+            http: HttpProbe::new(check.url.clone()),
+            check: check.clone(),
+        }
+    }
+}
+
+impl Probe for MetricProbe {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move {
+            let resp = self.http.get().await?;
+            if resp.status() != StatusCode::OK {
+                return Err(CheckError::Status(resp.status().as_u16()));
+            }
+            let body = resp
+                .into_body()
+                .collect()
+                .await
+                .map_err(|err| CheckError::Io(err.to_string()))?
+                .to_bytes();
+            self.check.evaluate(&String::from_utf8_lossy(&body))
+        })
+    }
+}
+
+impl FromStr for MetricOp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "eq" => Ok(Self::Eq),
+            "ne" => Ok(Self::Ne),
+            "lt" => Ok(Self::Lt),
+            "le" => Ok(Self::Le),
+            "gt" => Ok(Self::Gt),
+            "ge" => Ok(Self::Ge),
+            _ => Err(format!("'{s}' is not one of eq, ne, lt, le, gt or ge")),
+        }
+    }
+}
+
+impl fmt::Display for MetricOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eq => "eq",
+            Self::Ne => "ne",
+            Self::Lt => "lt",
+            Self::Le => "le",
+            Self::Gt => "gt",
+            Self::Ge => "ge",
+        }
+        .fmt(f)
+    }
+}
+
+impl FromStr for MetricCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(rest) = s
+            .strip_prefix("metric+")
+            .filter(|e| e.starts_with("http://") || e.starts_with("https://"))
+        else {
+            return Err(format!(
+                "'{s}' doesn't match metric+http(s)://<url>?name=<metric>&value=<threshold> pattern"
+            ));
+        };
+        let (base, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let mut selector = None;
+        let mut op = MetricOp::default();
+        let mut value = None;
+        // other parameters are a part of the scraped url
+        let mut params = vec![];
+        for pair in query.split('&').filter(|e| !e.is_empty()) {
+            let (_, mut options) = split_query(&format!("?{pair}"))?;
+            let (key, option) = options.remove(0);
+            match key.as_str() {
+                "name" => selector = Some(option),
+                "op" => {
+                    op = option
+                        .parse()
+                        .map_err(|err| format!("'{s}', op error: {err}"))?
+                }
+                "value" => {
+                    value = Some(
+                        option
+                            .parse::<f64>()
+                            .map_err(|_| format!("'{s}', '{option}' is not a number"))?,
+                    )
+                }
+                _ => params.push(pair),
+            }
+        }
+        let url = if params.is_empty() {
+            base.to_string()
+        } else {
+            format!("{base}?{}", params.join("&"))
+        };
+        let url: Uri = url.parse().map_err(|err| format!("'{s}': {err}"))?;
+
+        let selector = selector.ok_or_else(|| format!("'{s}', name option is missing"))?;
+        let (name, labels) = match selector.find('{') {
+            Some(idx) => match parse_labels(&selector[idx..]) {
+                Some((labels, "")) => (&selector[..idx], labels),
+                _ => return Err(format!("'{s}', invalid label selector '{selector}'")),
+            },
+            None => (selector.as_str(), vec![]),
+        };
+        let valid = name
+            .chars()
+            .all(|e| e.is_ascii_alphanumeric() || e == '_' || e == ':');
+        if name.is_empty() || !valid {
+            return Err(format!("'{s}', '{name}' is not a valid metric name"));
+        }
+
+        Ok(Self {
+            url,
+            name: name.to_string(),
+            labels,
+            op,
+            value: value.ok_or_else(|| format!("'{s}', value option is missing"))?,
+        })
+    }
+}
+
+impl fmt::Display for MetricCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.url.query().is_some() { '&' } else { '?' };
+        write!(
+            f,
+            "metric+{}{separator}name={}&op={}&value={}",
+            self.url,
+            percent_encode(&series(&self.name, &self.labels)),
+            self.op,
+            self.value
+        )
+    }
+}
@@ -0,0 +1,181 @@
+use std::{fmt, future::Future, path::PathBuf, pin::Pin, process, str::FromStr};
+
+use super::{Attempt, Probe};
+use crate::{split_query, CheckError};
+
+/// How the process is looked up
+#[derive(Debug, PartialEq, Clone)]
+pub enum ProcessTarget {
+    /// File containing the PID
+    PidFile(PathBuf),
+    Pid(u32),
+    /// Name of the executable
+    Name(String),
+}
+
+/// Local process which has to be running (or exited)
+///
+/// it may be parsed from `pid:<path to pid file>`, `pid:<pid>` or `process:<name>`
+/// followed by optional `?exited` to wait till the process exits instead.
+/// Numbers are PIDs, so a PID file named as a number is written as `pid:./<number>`.
+///
+/// Processes are looked up in `/proc` so this check works on Linux only.
+///
+/// ```
+/// use wait_for_them::{ProcessCheck, ProcessTarget};
+///
+/// let check: ProcessCheck = "process:nginx?exited".parse().unwrap();
+/// assert!(check.exited);
+///
+/// let check: ProcessCheck = "pid:./1234".parse().unwrap();
+/// assert_eq!(check.target, ProcessTarget::PidFile("./1234".into()));
+/// assert_eq!(check.to_string(), "pid:./1234");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProcessCheck {
+    pub target: ProcessTarget,
+    pub exited: bool,
+}
+
+impl ProcessCheck {
+    /// Returns PIDs of matching running processes
+    async fn find(&self) -> Result<Vec<u32>, String> {
+        match &self.target {
+            ProcessTarget::PidFile(path) => {
+                let content = match tokio::fs::read_to_string(path).await {
+                    Ok(content) => content,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+                    Err(err) => return Err(format!("'{}': {err}", path.display())),
+                };
+                let pid = content
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("'{}' doesn't contain a valid PID", path.display()))?;
+                Ok(is_alive(pid).await.then_some(pid).into_iter().collect())
+            }
+            ProcessTarget::Pid(pid) => {
+                Ok(is_alive(*pid).await.then_some(*pid).into_iter().collect())
+            }
+            ProcessTarget::Name(name) => {
+                let mut entries = tokio::fs::read_dir("/proc")
+                    .await
+                    .map_err(|err| format!("/proc: {err}"))?;
+                let mut pids = vec![];
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    let Some(pid) = entry.file_name().to_str().and_then(|e| e.parse().ok()) else {
+                        continue;
+                    };
+                    if pid != process::id() && has_name(pid, name).await && is_alive(pid).await {
+                        pids.push(pid);
+                    }
+                }
+                Ok(pids)
+            }
+        }
+    }
+}
+
+/// Checks that the process exists and is not a zombie
+async fn is_alive(pid: u32) -> bool {
+    let Ok(stat) = tokio::fs::read_to_string(format!("/proc/{pid}/stat")).await else {
+        return false;
+    };
+    // state follows the executable name which is enclosed in parentheses
+    let state = stat
+        .rsplit_once(')')
+        .and_then(|(_, rest)| rest.trim_start().chars().next());
+    !matches!(state, None | Some('Z') | Some('X'))
+}
+
+async fn has_name(pid: u32, name: &str) -> bool {
+    if let Ok(comm) = tokio::fs::read_to_string(format!("/proc/{pid}/comm")).await {
+        if comm.trim_end() == name {
+            return true;
+        }
+    }
+    // comm is truncated to 15 characters, so the executable path is checked as well
+    if let Ok(cmdline) = tokio::fs::read(format!("/proc/{pid}/cmdline")).await {
+        let executable = cmdline.split(|e| *e == 0).next().unwrap_or_default();
+        let executable = String::from_utf8_lossy(executable);
+        return executable.rsplit('/').next() == Some(name);
+    }
+    false
+}
+
+impl Probe for ProcessCheck {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move {
+            let pids = self.find().await.map_err(CheckError::Io)?;
+            let pids_str = pids
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            match (self.exited, pids.is_empty()) {
+                (false, false) => Ok(Some(format!("pid {pids_str}"))),
+                (false, true) => Err("process is not running".to_string().into()),
+                (true, false) => Err(format!("process is still running (pid {pids_str})").into()),
+                (true, true) => Ok(None),
+            }
+        })
+    }
+}
+
+impl FromStr for ProcessCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (base, query) = split_query(s)?;
+        let target = if let Some(pid) = base.strip_prefix("pid:") {
+            if pid.is_empty() {
+                return Err(format!("'{s}' doesn't match pid:<pid file> pattern"));
+            }
+            match pid.parse() {
+                Ok(0) => return Err(format!("'{s}', 0 is not a valid PID")),
+                Ok(pid) => ProcessTarget::Pid(pid),
+                Err(_) => ProcessTarget::PidFile(PathBuf::from(pid)),
+            }
+        } else if let Some(name) = base.strip_prefix("process:") {
+            if name.is_empty() {
+                return Err(format!("'{s}' doesn't match process:<name> pattern"));
+            }
+            ProcessTarget::Name(name.to_string())
+        } else {
+            return Err(format!(
+                "'{s}' doesn't match pid:<pid file> or process:<name> pattern"
+            ));
+        };
+
+        let mut check = Self {
+            target,
+            exited: false,
+        };
+        for (key, value) in query {
+            match key.as_str() {
+                "exited" => check.exited = value.is_empty() || value == "true",
+                _ => return Err(format!("'{s}', unknown option '{key}'")),
+            }
+        }
+        Ok(check)
+    }
+}
+
+impl fmt::Display for ProcessCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            // a bare number would be read back as a PID
+            ProcessTarget::PidFile(path)
+                if path.to_str().is_some_and(|e| e.parse::<u32>().is_ok()) =>
+            {
+                write!(f, "pid:./{}", path.display())?
+            }
+            ProcessTarget::PidFile(path) => write!(f, "pid:{}", path.display())?,
+            ProcessTarget::Pid(pid) => write!(f, "pid:{pid}")?,
+            ProcessTarget::Name(name) => write!(f, "process:{name}")?,
+        }
+        if self.exited {
+            write!(f, "?exited")?;
+        }
+        Ok(())
+    }
+}
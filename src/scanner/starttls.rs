@@ -0,0 +1,217 @@
+use std::{fmt, str::FromStr};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// `StartTLS` extended operation request with message id 1
+const LDAP_STARTTLS_REQUEST: &[u8] = b"\x30\x1d\x02\x01\x01\x77\x18\x80\x161.3.6.1.4.1.1466.20037";
+/// `SSLRequest` message - length 8 followed by the request code 80877103
+const POSTGRES_SSL_REQUEST: &[u8] = b"\x00\x00\x00\x08\x04\xd2\x16\x2f";
+/// Longest line accepted from the server
+const MAX_LINE_LENGTH: usize = 4096;
+
+/// Plaintext protocol which is upgraded to TLS before the handshake
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StartTls {
+    Smtp,
+    Imap,
+    Pop3,
+    Ldap,
+    /// `SSLRequest` of the PostgreSQL protocol
+    Postgres,
+}
+
+impl StartTls {
+    /// Checks the server banner and asks the server to upgrade the connection
+    pub(crate) async fn upgrade(&self, stream: &mut TcpStream) -> Result<(), String> {
+        match self {
+            Self::Smtp => {
+                expect_reply(stream, "220", "greeting").await?;
+                write(stream, b"EHLO wait-for-them\r\n").await?;
+                let ehlo = expect_reply(stream, "250", "EHLO").await?;
+                if !ehlo.iter().any(|line| {
+                    line.get(4..)
+                        .unwrap_or_default()
+                        .trim()
+                        .eq_ignore_ascii_case("STARTTLS")
+                }) {
+                    return Err("smtp: STARTTLS is not supported".to_string());
+                }
+                write(stream, b"STARTTLS\r\n").await?;
+                expect_reply(stream, "220", "STARTTLS").await?;
+            }
+            Self::Imap => {
+                let greeting = read_line(stream).await?;
+                if !greeting.starts_with("* OK") {
+                    return Err(format!("imap: unexpected greeting '{greeting}'"));
+                }
+                write(stream, b"a001 STARTTLS\r\n").await?;
+                loop {
+                    let line = read_line(stream).await?;
+                    if let Some(status) = line.strip_prefix("a001 ") {
+                        if !status.starts_with("OK") {
+                            return Err(format!("imap: STARTTLS refused '{status}'"));
+                        }
+                        break;
+                    }
+                }
+            }
+            Self::Pop3 => {
+                let greeting = read_line(stream).await?;
+                if !greeting.starts_with("+OK") {
+                    return Err(format!("pop3: unexpected greeting '{greeting}'"));
+                }
+                write(stream, b"STLS\r\n").await?;
+                let reply = read_line(stream).await?;
+                if !reply.starts_with("+OK") {
+                    return Err(format!("pop3: STLS refused '{reply}'"));
+                }
+            }
+            Self::Ldap => {
+                write(stream, LDAP_STARTTLS_REQUEST).await?;
+                let code = read_ldap_result_code(stream).await?;
+                if code != 0 {
+                    return Err(format!("ldap: StartTLS refused with result code {code}"));
+                }
+            }
+            Self::Postgres => {
+                write(stream, POSTGRES_SSL_REQUEST).await?;
+                match stream.read_u8().await.map_err(|err| err.to_string())? {
+                    b'S' => {}
+                    b'N' => return Err("postgres: SSL is not supported".to_string()),
+                    other => return Err(format!("postgres: unexpected reply {other:#04x}")),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn write(stream: &mut TcpStream, data: &[u8]) -> Result<(), String> {
+    stream.write_all(data).await.map_err(|err| err.to_string())
+}
+
+/// Reads a single line without the line ending
+///
+/// It reads byte by byte so that nothing which belongs to the TLS handshake is consumed.
+async fn read_line(stream: &mut TcpStream) -> Result<String, String> {
+    let mut line = vec![];
+    loop {
+        match stream.read_u8().await.map_err(|err| err.to_string())? {
+            b'\n' => break,
+            byte => line.push(byte),
+        }
+        if line.len() > MAX_LINE_LENGTH {
+            return Err("line sent by the server is too long".to_string());
+        }
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+/// Reads (possibly multiline) SMTP reply and checks its code
+async fn expect_reply(
+    stream: &mut TcpStream,
+    code: &str,
+    context: &str,
+) -> Result<Vec<String>, String> {
+    let mut lines = vec![];
+    loop {
+        let line = read_line(stream).await?;
+        if line.len() < 4 || !line.starts_with(code) {
+            return Err(format!("smtp: unexpected {context} reply '{line}'"));
+        }
+        let last = line.as_bytes()[3] == b' ';
+        lines.push(line);
+        if last {
+            return Ok(lines);
+        }
+    }
+}
+
+async fn read_ber_length(stream: &mut TcpStream) -> Result<usize, String> {
+    let first = stream.read_u8().await.map_err(|err| err.to_string())?;
+    if first & 0x80 == 0 {
+        return Ok(first as usize);
+    }
+    let count = first & 0x7f;
+    if count == 0 || count > 4 {
+        return Err("ldap: unsupported length encoding".to_string());
+    }
+    let mut length = 0_usize;
+    for _ in 0..count {
+        length = (length << 8) | stream.read_u8().await.map_err(|err| err.to_string())? as usize;
+    }
+    Ok(length)
+}
+
+/// Reads `ExtendedResponse` and returns its result code
+async fn read_ldap_result_code(stream: &mut TcpStream) -> Result<u8, String> {
+    let malformed = || "ldap: malformed response".to_string();
+
+    if stream.read_u8().await.map_err(|err| err.to_string())? != 0x30 {
+        return Err(malformed());
+    }
+    let length = read_ber_length(stream).await?;
+    if length > MAX_LINE_LENGTH {
+        return Err(malformed());
+    }
+    let mut message = vec![0; length];
+    stream
+        .read_exact(&mut message)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    // messageID INTEGER
+    let [0x02, id_length, rest @ ..] = message.as_slice() else {
+        return Err(malformed());
+    };
+    let rest = rest.get(*id_length as usize..).ok_or_else(malformed)?;
+    // [APPLICATION 24] ExtendedResponse with short or long length
+    let [0x78, first, rest @ ..] = rest else {
+        return Err(malformed());
+    };
+    let skip = if first & 0x80 == 0 {
+        0
+    } else {
+        (first & 0x7f) as usize
+    };
+    // resultCode ENUMERATED
+    match rest.get(skip..skip + 3) {
+        Some([0x0a, 0x01, code]) => Ok(*code),
+        _ => Err(malformed()),
+    }
+}
+
+impl FromStr for StartTls {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smtp" => Ok(Self::Smtp),
+            "imap" => Ok(Self::Imap),
+            "pop3" => Ok(Self::Pop3),
+            "ldap" => Ok(Self::Ldap),
+            "postgres" => Ok(Self::Postgres),
+            _ => Err(format!(
+                "'{s}' is not one of smtp, imap, pop3, ldap or postgres"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for StartTls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Smtp => "smtp",
+            Self::Imap => "imap",
+            Self::Pop3 => "pop3",
+            Self::Ldap => "ldap",
+            Self::Postgres => "postgres",
+        }
+        .fmt(f)
+    }
+}
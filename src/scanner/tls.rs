@@ -0,0 +1,273 @@
+use native_tls::{Certificate, TlsConnector};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt,
+    future::Future,
+    net::IpAddr,
+    path::PathBuf,
+    pin::Pin,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::net::TcpStream;
+use x509_parser::{certificate::X509Certificate, extensions::GeneralName, prelude::FromDer};
+
+use super::{connect, starttls::StartTls, Attempt, Probe};
+use crate::{parse_host_and_port, percent_encode, split_form_query, CheckError};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// TLS endpoint which is going to be verified
+///
+/// it may be parsed from `tls://<hostname>:<port>[?option=value[&option=value...]]`
+/// where the options are:
+///
+/// * `sni` - server name sent during the handshake (hostname is used by default)
+/// * `name` - name which has to be covered by the certificate
+/// * `days` - minimal number of days till the certificate expires
+/// * `fingerprint` - pinned SHA-256 fingerprint of the certificate in hex
+/// * `ca` - path to a PEM file with an additional trusted certificate
+/// * `insecure` - skip the certificate chain and hostname validation
+/// * `starttls` - upgrade plaintext connection first (`smtp`, `imap`, `pop3`, `ldap` or `postgres`)
+///
+/// The values are percent-decoded and `+` stands for a space.
+///
+/// ```
+/// let check: wait_for_them::TlsCheck = "tls://ldap.example.com:636?days=14".parse().unwrap();
+/// assert_eq!(check.days, Some(14));
+///
+/// let check: wait_for_them::TlsCheck = "tls://db:5432?ca=/my+certs/ca%2B.pem".parse().unwrap();
+/// assert_eq!(check.ca, Some("/my certs/ca+.pem".into()));
+///
+/// let check: wait_for_them::TlsCheck = "tls://db:5432?name=a%26b%3Dc&ca=/my+certs/ca%2B.pem"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(check.to_string().parse::<wait_for_them::TlsCheck>(), Ok(check));
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct TlsCheck {
+    pub host: String,
+    pub port: u16,
+    pub sni: Option<String>,
+    pub name: Option<String>,
+    pub days: Option<u32>,
+    /// lowercase hex without separators
+    pub fingerprint: Option<String>,
+    pub ca: Option<PathBuf>,
+    pub insecure: bool,
+    pub starttls: Option<StartTls>,
+}
+
+impl TlsCheck {
+    /// Performs the handshake over an established connection and verifies the certificate
+    pub(crate) async fn handshake(&self, stream: TcpStream) -> Result<Option<String>, String> {
+        let mut builder = TlsConnector::builder();
+        if self.insecure {
+            builder
+                .danger_accept_invalid_certs(true)
+                .danger_accept_invalid_hostnames(true);
+        }
+        if let Some(ca) = &self.ca {
+            let pem = tokio::fs::read(ca)
+                .await
+                .map_err(|err| format!("'{}': {err}", ca.display()))?;
+            builder.add_root_certificate(Certificate::from_pem(&pem).map_err(|e| e.to_string())?);
+        }
+        let connector =
+            tokio_native_tls::TlsConnector::from(builder.build().map_err(|e| e.to_string())?);
+
+        let domain = self.sni.as_deref().unwrap_or(&self.host);
+        let stream = connector
+            .connect(domain, stream)
+            .await
+            .map_err(|err| format!("handshake failed: {err}"))?;
+
+        let der = stream
+            .get_ref()
+            .peer_certificate()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "no certificate presented".to_string())?
+            .to_der()
+            .map_err(|e| e.to_string())?;
+
+        self.verify(&der)
+    }
+
+    fn verify(&self, der: &[u8]) -> Result<Option<String>, String> {
+        let (_, cert) =
+            X509Certificate::from_der(der).map_err(|err| format!("invalid certificate: {err}"))?;
+
+        let fingerprint: String = Sha256::digest(der)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        if let Some(pinned) = &self.fingerprint {
+            if *pinned != fingerprint {
+                return Err(format!("fingerprint mismatch (sha256 {fingerprint})"));
+            }
+        }
+
+        let not_after = cert.validity().not_after;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|e| e.as_secs() as i64)
+            .unwrap_or_default();
+        let remaining_days = (not_after.timestamp() - now).div_euclid(SECONDS_PER_DAY);
+        if let Some(days) = self.days {
+            if not_after.timestamp() - now < i64::from(days) * SECONDS_PER_DAY {
+                return Err(format!(
+                    "certificate expires in {remaining_days} days ({not_after})"
+                ));
+            }
+        }
+
+        if let Some(name) = &self.name {
+            if !certificate_names(&cert).iter().any(|e| covers(e, name)) {
+                return Err(format!("certificate doesn't cover '{name}'"));
+            }
+        }
+
+        Ok(Some(format!(
+            "{}, expires {not_after} ({remaining_days} days), sha256 {fingerprint}",
+            cert.subject()
+        )))
+    }
+}
+
+impl Probe for TlsCheck {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move {
+            let mut stream = connect(&self.host, self.port).await?;
+            if let Some(starttls) = self.starttls {
+                starttls
+                    .upgrade(&mut stream)
+                    .await
+                    .map_err(CheckError::Tls)?;
+            }
+            self.handshake(stream).await.map_err(CheckError::Tls)
+        })
+    }
+}
+
+/// Lists DNS names and IP addresses from subject alternative names and common names
+fn certificate_names(cert: &X509Certificate) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        for name in &san.value.general_names {
+            match name {
+                GeneralName::DNSName(dns) => names.push(dns.to_string()),
+                GeneralName::IPAddress(ip) => match ip.len() {
+                    4 => names.push(IpAddr::from(<[u8; 4]>::try_from(*ip).unwrap()).to_string()),
+                    16 => names.push(IpAddr::from(<[u8; 16]>::try_from(*ip).unwrap()).to_string()),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+    names.extend(
+        cert.subject()
+            .iter_common_name()
+            .filter_map(|e| e.as_str().ok())
+            .map(String::from),
+    );
+    names
+}
+
+/// Checks whether certificate name (possibly a wildcard) covers the given name
+fn covers(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    let name = name.to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => name
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+        None => pattern == name,
+    }
+}
+
+impl FromStr for TlsCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(rest) = s.strip_prefix("tls://") else {
+            return Err(format!(
+                "'{s}' doesn't match tls://<hostname>:<port> pattern"
+            ));
+        };
+        let (host_and_port, query) = split_form_query(rest)?;
+        let (host, port) = parse_host_and_port(host_and_port)?;
+
+        let mut check = Self {
+            host,
+            port,
+            ..Default::default()
+        };
+        for (key, value) in query {
+            match key.as_str() {
+                "sni" => check.sni = Some(value),
+                "name" => check.name = Some(value),
+                "days" => {
+                    check.days = Some(
+                        value
+                            .parse()
+                            .map_err(|err| format!("'{s}', days error: {err}"))?,
+                    )
+                }
+                "fingerprint" => {
+                    let fingerprint = value.replace(':', "").to_ascii_lowercase();
+                    if fingerprint.len() != 64
+                        || !fingerprint.chars().all(|e| e.is_ascii_hexdigit())
+                    {
+                        return Err(format!("'{s}', fingerprint is not a SHA-256 hex digest"));
+                    }
+                    check.fingerprint = Some(fingerprint);
+                }
+                "ca" => check.ca = Some(PathBuf::from(value)),
+                "insecure" => check.insecure = value.is_empty() || value == "true",
+                "starttls" => {
+                    check.starttls = Some(
+                        value
+                            .parse()
+                            .map_err(|err| format!("'{s}', starttls error: {err}"))?,
+                    )
+                }
+                _ => return Err(format!("'{s}', unknown option '{key}'")),
+            }
+        }
+        Ok(check)
+    }
+}
+
+impl fmt::Display for TlsCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut options = vec![];
+        if let Some(sni) = &self.sni {
+            options.push(format!("sni={}", percent_encode(sni)));
+        }
+        if let Some(name) = &self.name {
+            options.push(format!("name={}", percent_encode(name)));
+        }
+        if let Some(days) = self.days {
+            options.push(format!("days={days}"));
+        }
+        if let Some(fingerprint) = &self.fingerprint {
+            options.push(format!("fingerprint={fingerprint}"));
+        }
+        if let Some(ca) = &self.ca {
+            options.push(format!("ca={}", percent_encode(&ca.to_string_lossy())));
+        }
+        if self.insecure {
+            options.push("insecure".to_string());
+        }
+        if let Some(starttls) = self.starttls {
+            options.push(format!("starttls={starttls}"));
+        }
+
+        write!(f, "tls://{}:{}", self.host, self.port)?;
+        if !options.is_empty() {
+            write!(f, "?{}", options.join("&"))?;
+        }
+        Ok(())
+    }
+}
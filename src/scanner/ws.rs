@@ -0,0 +1,137 @@
+use futures::{SinkExt, StreamExt};
+use regex::Regex;
+use std::{fmt, future::Future, pin::Pin, str::FromStr};
+use tokio_tungstenite::tungstenite::{http::Uri, Error, Message};
+
+use super::{Attempt, Probe};
+use crate::{split_query, CheckError};
+
+/// WebSocket endpoint which is going to be verified
+///
+/// it may be parsed from `ws://<url>[#option=value[&option=value]]`
+/// or `wss://<url>[#...]` where the options are:
+///
+/// * `send` - text message sent once the connection is upgraded
+/// * `expect` - regex which has to match a received message
+///
+/// Options are placed into the fragment, so that the query of the url
+/// is sent to the server untouched.
+///
+/// ```
+/// let check: wait_for_them::WsCheck = "ws://localhost:8080/socket#send=ping&expect=pong"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(check.send.as_deref(), Some("ping"));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct WsCheck {
+    pub url: String,
+    pub send: Option<String>,
+    pub expect: Option<String>,
+}
+
+pub(crate) struct WsProbe {
+    url: String,
+    send: Option<String>,
+    expect: Option<Regex>,
+}
+
+impl WsProbe {
+    pub fn new(check: &WsCheck) -> Self {
+        Self {
+            url: check.url.clone(),
+            send: check.send.clone(),
+            // validated while parsing
+            expect: check.expect.as_deref().map(|e| Regex::new(e).unwrap()),
+        }
+    }
+}
+
+impl Probe for WsProbe {
+    fn attempt(&mut self) -> Pin<Box<dyn Future<Output = Attempt> + Send + '_>> {
+        Box::pin(async move {
+            let (mut stream, _) = tokio_tungstenite::connect_async(self.url.as_str())
+                .await
+                .map_err(|err| match err {
+                    Error::Http(resp) => CheckError::Status(resp.status().as_u16()),
+                    Error::Io(err) => err.into(),
+                    Error::Tls(err) => CheckError::Tls(err.to_string()),
+                    err => CheckError::Io(err.to_string()),
+                })?;
+
+            if let Some(send) = &self.send {
+                stream
+                    .send(Message::text(send.as_str()))
+                    .await
+                    .map_err(|err| CheckError::Io(err.to_string()))?;
+            }
+
+            if let Some(regex) = &self.expect {
+                loop {
+                    let message = stream
+                        .next()
+                        .await
+                        .ok_or_else(|| CheckError::Io("connection closed".to_string()))?
+                        .map_err(|err| CheckError::Io(err.to_string()))?;
+                    if !message.is_text() && !message.is_binary() {
+                        continue;
+                    }
+                    if regex.is_match(&String::from_utf8_lossy(&message.into_data())) {
+                        break;
+                    }
+                }
+            }
+
+            let _ = stream.close(None).await;
+            Ok(None)
+        })
+    }
+}
+
+impl FromStr for WsCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.starts_with("ws://") && !s.starts_with("wss://") {
+            return Err(format!("'{s}' doesn't match ws(s)://<url> pattern"));
+        }
+        let (url, fragment) = s.split_once('#').unwrap_or((s, ""));
+        url.parse::<Uri>().map_err(|err| format!("'{s}': {err}"))?;
+
+        let mut check = Self {
+            url: url.to_string(),
+            send: None,
+            expect: None,
+        };
+        let (_, options) = split_query(&format!("?{fragment}"))?;
+        for (key, value) in options {
+            match key.as_str() {
+                "send" => check.send = Some(value),
+                "expect" => {
+                    Regex::new(&value).map_err(|err| format!("'{s}', expect error: {err}"))?;
+                    check.expect = Some(value);
+                }
+                _ => return Err(format!("'{s}', unknown option '{key}'")),
+            }
+        }
+        Ok(check)
+    }
+}
+
+impl fmt::Display for WsCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut options = vec![];
+        if let Some(send) = &self.send {
+            options.push(format!("send={send}"));
+        }
+        if let Some(expect) = &self.expect {
+            options.push(format!("expect={expect}"));
+        }
+
+        self.url.fmt(f)?;
+        if !options.is_empty() {
+            write!(f, "#{}", options.join("&"))?;
+        }
+        Ok(())
+    }
+}
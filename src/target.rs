@@ -0,0 +1,251 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use crate::{parse_duration, RetryPolicy, ToCheck};
+
+/// Options which alter how a single item is waited for
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct TargetOptions {
+    /// Time limit of waiting for the item
+    pub timeout: Option<Duration>,
+    /// Failure of the item is an error (`true` by default)
+    pub required: Option<bool>,
+    /// Number of consecutive successful attempts required
+    pub stable: Option<u32>,
+    /// Time for which the attempts have to keep succeeding
+    pub stable_for: Option<Duration>,
+    /// Delays between the attempts
+    pub retry: Option<RetryPolicy>,
+    /// Time limit of a single attempt, depends on the kind of the item by default
+    pub attempt_timeout: Option<Duration>,
+    /// Name which other items may depend on
+    pub name: Option<String>,
+    /// Names of the items or groups which have to be ready before the item is checked
+    pub after: Vec<String>,
+}
+
+impl TargetOptions {
+    const KEYS: &'static [&'static str] = &[
+        "timeout",
+        "required",
+        "stable",
+        "stable_for",
+        "retry",
+        "attempt_timeout",
+        "name",
+        "after",
+    ];
+
+    pub fn is_required(&self) -> bool {
+        self.required.unwrap_or(true)
+    }
+
+    /// Fills unset options from `defaults`
+    pub fn or(self, defaults: &Self) -> Self {
+        Self {
+            timeout: self.timeout.or(defaults.timeout),
+            required: self.required.or(defaults.required),
+            stable: self.stable.or(defaults.stable),
+            stable_for: self.stable_for.or(defaults.stable_for),
+            retry: self.retry.or(defaults.retry),
+            attempt_timeout: self.attempt_timeout.or(defaults.attempt_timeout),
+            name: self.name,
+            after: self.after,
+        }
+    }
+
+    pub(crate) fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "timeout" => self.timeout = Some(parse_duration(value)?),
+            "required" => {
+                self.required = Some(match value {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(format!("required '{value}' is not true or false")),
+                })
+            }
+            "stable" => {
+                self.stable = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|e| *e > 0)
+                        .ok_or_else(|| format!("stable '{value}' is not a positive number"))?,
+                )
+            }
+            "stable_for" => self.stable_for = Some(parse_duration(value)?),
+            "retry" => self.retry = Some(value.parse()?),
+            "attempt_timeout" => {
+                let attempt_timeout = parse_duration(value)?;
+                if attempt_timeout.is_zero() {
+                    return Err("attempt_timeout has to be positive".to_string());
+                }
+                self.attempt_timeout = Some(attempt_timeout);
+            }
+            "name" => self.name = Some(parse_name(value)?),
+            "after" => self.after.push(parse_name(value)?),
+            _ => return Err(format!("unknown option '{key}'")),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TargetOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut options = vec![];
+        if let Some(timeout) = self.timeout {
+            options.push(format!("timeout={}ms", timeout.as_millis()));
+        }
+        if let Some(required) = self.required {
+            options.push(format!("required={required}"));
+        }
+        if let Some(stable) = self.stable {
+            options.push(format!("stable={stable}"));
+        }
+        if let Some(stable_for) = self.stable_for {
+            options.push(format!("stable_for={}ms", stable_for.as_millis()));
+        }
+        if let Some(retry) = self.retry {
+            options.push(format!("retry={retry}"));
+        }
+        if let Some(attempt_timeout) = self.attempt_timeout {
+            options.push(format!("attempt_timeout={}ms", attempt_timeout.as_millis()));
+        }
+        if let Some(name) = &self.name {
+            options.push(format!("name={name}"));
+        }
+        for after in &self.after {
+            options.push(format!("after={after}"));
+        }
+        options.join(",").fmt(f)
+    }
+}
+
+/// Validates a name of an item or a group
+///
+/// ```
+/// assert_eq!(wait_for_them::parse_name("db-1"), Ok("db-1".to_string()));
+/// assert!(wait_for_them::parse_name("db.1").is_err());
+/// ```
+pub fn parse_name(value: &str) -> Result<String, String> {
+    let valid = value
+        .chars()
+        .all(|e| e.is_ascii_alphanumeric() || e == '_' || e == '-');
+    if value.is_empty() || !valid {
+        return Err(format!(
+            "'{value}' is not a valid name, use letters, digits, '_' and '-'"
+        ));
+    }
+    Ok(value.to_string())
+}
+
+/// Item to be checked together with its options
+///
+/// it may be parsed from `<item>[@option=value[,option=value]]` where the options are:
+///
+/// * `timeout` - time limit of waiting for the item
+/// * `required` - `false` when the failure of the item is not an error
+/// * `stable` - number of consecutive successful attempts required
+/// * `stable_for` - time for which the attempts have to keep succeeding
+/// * `retry` - [`RetryPolicy`] e.g. `exponential:100:5000`
+/// * `attempt_timeout` - time limit of a single attempt
+/// * `name` - name which other items may depend on
+/// * `after` - name of an item or a group which has to be ready first,
+///   may be repeated to depend on several of them
+///
+/// Times are parsed using [`parse_duration`](crate::parse_duration).
+/// Options which are specific to a kind of the item are a part of the item itself
+/// (e.g. `tls://host:443?days=14`).
+///
+/// The suffix is treated as options only when it looks like `key=value[,key=value]`,
+/// so that `@` can still be used within the items (e.g. `mqtt://user@host`).
+/// Unknown keys of such suffix are reported (e.g. `exec:foo@timout=5s`).
+///
+/// ```
+/// let target: wait_for_them::Target = "localhost:5432@timeout=120s,stable=3".parse().unwrap();
+/// assert_eq!(target.options.timeout, Some(std::time::Duration::from_secs(120)));
+/// assert_eq!(target.options.stable, Some(3));
+/// assert_eq!(target.to_check.to_string(), "localhost:5432");
+/// assert_eq!(target.to_string(), "localhost:5432@timeout=120000ms,stable=3");
+/// assert_eq!(target.to_string().parse::<wait_for_them::Target>(), Ok(target));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Target {
+    pub to_check: ToCheck,
+    pub options: TargetOptions,
+}
+
+impl From<ToCheck> for Target {
+    fn from(to_check: ToCheck) -> Self {
+        Self {
+            to_check,
+            options: TargetOptions::default(),
+        }
+    }
+}
+
+/// Splits `key=value,key=value` when all the keys are known
+fn split_options(suffix: &str) -> Option<Vec<(&str, &str)>> {
+    suffix
+        .split(',')
+        .map(|pair| {
+            pair.split_once('=')
+                .filter(|(key, _)| TargetOptions::KEYS.contains(key))
+        })
+        .collect()
+}
+
+/// Finds the first unknown key when the suffix looks like `key=value,key=value`
+fn unknown_option(suffix: &str) -> Option<&str> {
+    let keys: Option<Vec<&str>> = suffix
+        .split(',')
+        .map(|pair| {
+            pair.split_once('=').map(|(key, _)| key).filter(|key| {
+                !key.is_empty() && key.bytes().all(|e| e.is_ascii_lowercase() || e == b'_')
+            })
+        })
+        .collect();
+    keys?
+        .into_iter()
+        .find(|key| !TargetOptions::KEYS.contains(key))
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut options = TargetOptions::default();
+        let spec = match s.rsplit_once('@') {
+            Some((spec, suffix)) => match split_options(suffix) {
+                Some(pairs) => {
+                    for (key, value) in pairs {
+                        options
+                            .set(key, value)
+                            .map_err(|err| format!("'{s}', {err}"))?;
+                    }
+                    spec
+                }
+                None => match unknown_option(suffix) {
+                    // a misspelled option rather than an item which contains `@`
+                    Some(key) => return Err(format!("'{s}', unknown option '{key}'")),
+                    None => s,
+                },
+            },
+            None => s,
+        };
+
+        Ok(Self {
+            to_check: spec.parse()?,
+            options,
+        })
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_check.fmt(f)?;
+        if self.options != TargetOptions::default() {
+            write!(f, "@{}", self.options)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,298 @@
+use futures::future::{FutureExt, Shared};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    check_dependencies, dependency::Signals, scanner, Backoff, Group, Output, Quorum, RetryPolicy,
+    Target, TargetOptions, TargetResult,
+};
+
+type Cancel = Shared<Pin<Box<dyn Future<Output = ()> + Send>>>;
+type NewBackoff = Arc<dyn Fn() -> Box<dyn Backoff> + Send + Sync>;
+
+/// Waits for the items and the groups of items
+///
+/// It is created by [`Waiter::builder`].
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use wait_for_them::{Group, Output, Quorum, RetryPolicy, Waiter};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let waiter = Waiter::builder()
+///         .target("localhost:5432".parse().unwrap())
+///         .target("http://localhost:8080/health@stable=3".parse().unwrap())
+///         .group(Group {
+///             name: Some("cache".into()),
+///             quorum: Quorum::Any,
+///             targets: vec!["redis-a:6379".parse().unwrap(), "redis-b:6379".parse().unwrap()],
+///         })
+///         .timeout(Duration::from_secs(30))
+///         .retry(RetryPolicy::Exponential {
+///             initial: Duration::from_millis(100),
+///             max: Duration::from_secs(5),
+///         })
+///         .output(Output::Silent)
+///         .cancel_on(tokio::time::sleep(Duration::from_secs(10)))
+///         .build()
+///         .unwrap();
+///     let groups = waiter.groups().to_vec();
+///     let res = waiter.wait().await;
+///     let missing: usize = groups.iter().zip(&res).map(|(group, res)| group.missing(res)).sum();
+/// }
+/// ```
+pub struct Waiter {
+    /// the items which are not in any group come first
+    groups: Vec<Group>,
+    defaults: TargetOptions,
+    backoff: Option<NewBackoff>,
+    output: Output,
+    start_time: Option<Instant>,
+    cancel: Option<Cancel>,
+}
+
+/// Builder of [`Waiter`]
+#[derive(Default)]
+pub struct WaiterBuilder {
+    targets: Vec<Target>,
+    quorum: Quorum,
+    groups: Vec<Group>,
+    defaults: TargetOptions,
+    backoff: Option<NewBackoff>,
+    output: Output,
+    start_time: Option<Instant>,
+    cancel: Option<Cancel>,
+}
+
+impl WaiterBuilder {
+    /// Adds an item which is not in any group
+    pub fn target(mut self, target: Target) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    /// Adds items which are not in any group
+    pub fn targets(mut self, targets: impl IntoIterator<Item = Target>) -> Self {
+        self.targets.extend(targets);
+        self
+    }
+
+    /// Quorum of the items which are not in any group, all of them by default
+    pub fn quorum(mut self, quorum: Quorum) -> Self {
+        self.quorum = quorum;
+        self
+    }
+
+    pub fn group(mut self, group: Group) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    pub fn groups(mut self, groups: impl IntoIterator<Item = Group>) -> Self {
+        self.groups.extend(groups);
+        self
+    }
+
+    /// Time limit of the items which don't set their own, they may wait forever by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.defaults.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry policy of the items which don't set their own
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.defaults.retry = Some(retry);
+        self
+    }
+
+    /// Custom delays between the attempts of the items which don't set their own `retry`,
+    /// takes precedence over [`retry`](Self::retry)
+    ///
+    /// A new [`Backoff`] is created for each item.
+    pub fn backoff<B: Backoff + 'static>(
+        mut self,
+        backoff: impl Fn() -> B + Send + Sync + 'static,
+    ) -> Self {
+        self.backoff = Some(Arc::new(move || Box::new(backoff())));
+        self
+    }
+
+    /// Options of the items which don't set them, overrides [`timeout`](Self::timeout)
+    /// and [`retry`](Self::retry) set before
+    pub fn options(mut self, options: TargetOptions) -> Self {
+        self.defaults = options;
+        self
+    }
+
+    /// How the progress and the results are reported, progress by default
+    pub fn output(mut self, output: Output) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Time which the elapsed times are measured from, the start of [`Waiter::wait`] by default
+    pub fn start_time(mut self, start_time: Instant) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// Stops the waiting once the future is done,
+    /// the items which are not done by then are reported as cancelled
+    pub fn cancel_on(mut self, cancel: impl Future<Output = ()> + Send + 'static) -> Self {
+        let cancel: Pin<Box<dyn Future<Output = ()> + Send>> = Box::pin(cancel);
+        self.cancel = Some(cancel.shared());
+        self
+    }
+
+    /// Verifies the quorum and the dependencies of the items
+    pub fn build(self) -> Result<Waiter, String> {
+        if self.quorum.needed(self.targets.len()) > self.targets.len() {
+            return Err(format!(
+                "At least {} items are required, but only {} are set",
+                self.quorum,
+                self.targets.len()
+            ));
+        }
+        let ungrouped = Group {
+            name: None,
+            quorum: self.quorum,
+            targets: self.targets,
+        };
+        let groups: Vec<Group> = [ungrouped].into_iter().chain(self.groups).collect();
+        check_dependencies(&groups)?;
+        Ok(Waiter {
+            groups,
+            defaults: self.defaults,
+            backoff: self.backoff,
+            output: self.output,
+            start_time: self.start_time,
+            cancel: self.cancel,
+        })
+    }
+}
+
+impl Waiter {
+    pub fn builder() -> WaiterBuilder {
+        WaiterBuilder::default()
+    }
+
+    /// The items which are not in any group followed by the groups
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    /// Waits for the groups, each group till its [`Quorum`] is reached
+    ///
+    /// Items are checked once the items and groups named in their `after` option are ready
+    /// and they fail when any of those fails. Waiting for the dependencies counts
+    /// towards the timeout of the item.
+    ///
+    /// The future is `Send`, so it may be spawned on a multi-threaded runtime.
+    ///
+    /// # Returns
+    /// results of the items for each of [`groups`](Self::groups)
+    pub async fn wait(self) -> Vec<Vec<TargetResult>> {
+        let start_time = self.start_time.unwrap_or_else(Instant::now);
+        let output = self.output;
+        let targets: Vec<Target> = self
+            .groups
+            .iter()
+            .flat_map(|group| &group.targets)
+            .map(|target| Target {
+                to_check: target.to_check.clone(),
+                options: target.options.clone().or(&self.defaults),
+            })
+            .collect();
+        let backoffs: Vec<Box<dyn Backoff>> = self
+            .groups
+            .iter()
+            .flat_map(|group| &group.targets)
+            .map(|target| match (target.options.retry, &self.backoff) {
+                (None, Some(backoff)) => backoff(),
+                (retry, _) => Box::new(retry.or(self.defaults.retry).unwrap_or_default().backoff()),
+            })
+            .collect();
+        let signals = Signals::new(&self.groups);
+        #[cfg(feature = "json")]
+        let mut records = vec![];
+        let futures = match output {
+            Output::Progress => scanner::wait(&targets, signals.after, backoffs, start_time),
+            Output::Silent => scanner::wait_silent(&targets, signals.after, backoffs, start_time),
+            #[cfg(feature = "json")]
+            Output::Json | Output::Ndjson => {
+                let futures;
+                let stream = output == Output::Ndjson;
+                (futures, records) =
+                    scanner::wait_json(&targets, signals.after, backoffs, start_time, stream);
+                futures
+            }
+        };
+        let target_signals: Vec<_> = signals
+            .targets
+            .into_iter()
+            .map(|e| e.map(Arc::new))
+            .collect();
+        let mut futures =
+            futures
+                .into_iter()
+                .zip(target_signals.clone())
+                .map(|(future, signal)| match signal {
+                    Some(signal) => Box::pin(async move {
+                        let res = future.await;
+                        signal.send_replace(Some(res.is_ready()));
+                        res
+                    }),
+                    None => future,
+                });
+        let mut target_signals = target_signals.into_iter();
+
+        let cancel = self.cancel.unwrap_or_else(|| {
+            let never: Pin<Box<dyn Future<Output = ()> + Send>> =
+                Box::pin(futures::future::pending());
+            never.shared()
+        });
+        let waits = self
+            .groups
+            .iter()
+            .zip(signals.groups)
+            .map(|(group, signal)| {
+                let futures = futures.by_ref().take(group.targets.len()).collect();
+                let member_signals: Vec<_> = target_signals
+                    .by_ref()
+                    .take(group.targets.len())
+                    .flatten()
+                    .collect();
+                let cancel = cancel.clone();
+                async move {
+                    let res = group.wait(futures, start_time, cancel).await;
+                    let ready = group.missing(&res) == 0;
+                    // cancelled members which others depend on resolve to the result of the group
+                    for member in member_signals {
+                        member.send_if_modified(|state| {
+                            let cancelled = state.is_none();
+                            if cancelled {
+                                *state = Some(ready);
+                            }
+                            cancelled
+                        });
+                    }
+                    if let Some(signal) = signal {
+                        signal.send_replace(Some(ready));
+                    }
+                    res
+                }
+            });
+        let res = futures::future::join_all(waits).await;
+        #[cfg(feature = "json")]
+        if matches!(output, Output::Json | Output::Ndjson) {
+            scanner::report_json(&records, output == Output::Ndjson);
+        }
+        res
+    }
+}
@@ -0,0 +1,87 @@
+use assert_cmd::Command;
+use std::{fs, path::PathBuf, time::Duration};
+
+mod common;
+
+fn compose_path(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("wait-for-them-compose-{name}.yml"));
+    fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn compose_service() {
+    let server = common::TestServer::new(4870, Duration::from_millis(10));
+
+    // service names are used as hostnames
+    let path = compose_path(
+        "service",
+        "
+services:
+  localhost:
+    image: postgres
+    ports:
+      - target: 4870
+        published: 15432
+      - 4871/udp
+  web:
+    image: nginx
+    depends_on:
+      localhost:
+        condition: service_started
+  unrelated:
+    image: redis
+    expose: [4872]
+",
+    );
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("-t")
+        .arg("5s")
+        .arg("--from-compose")
+        .arg(&path)
+        .arg("--service")
+        .arg("web");
+    cmd.assert().success();
+
+    // all the services are used without --service
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd.arg("-t").arg("500ms").arg("--from-compose").arg(&path);
+    cmd.assert().failure().code(1);
+
+    drop(server);
+}
+
+#[test]
+fn compose_errors() {
+    let path = compose_path(
+        "errors",
+        "
+services:
+  db:
+    image: postgres
+  web:
+    image: nginx
+    depends_on: [db]
+",
+    );
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--from-compose")
+        .arg(&path)
+        .arg("--service")
+        .arg("web");
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    assert!(
+        String::from_utf8_lossy(&output).contains("service 'db' has neither ports nor healthcheck")
+    );
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--from-compose")
+        .arg(&path)
+        .arg("--service")
+        .arg("missing");
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    assert!(String::from_utf8_lossy(&output).contains("service 'missing' is not defined"));
+}
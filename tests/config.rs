@@ -0,0 +1,115 @@
+use assert_cmd::Command;
+use std::{fs, path::PathBuf, time::Duration};
+
+mod common;
+
+fn config_path(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("wait-for-them-config-{name}"));
+    fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn config_formats() {
+    let server = common::TestServer::new(4850, Duration::from_millis(10));
+
+    let toml = config_path(
+        "ok.toml",
+        r#"
+timeout = "5s"
+targets = [
+    "localhost:4850@name=server",
+    { item = "localhost:4850", after = "server", stable = 2 },
+]
+"#,
+    );
+    let yaml = config_path(
+        "ok.yaml",
+        "
+timeout: 5s
+groups:
+  - name: servers
+    quorum: any
+    targets: [localhost:4851, localhost:4850]
+",
+    );
+    let json = config_path(
+        "ok.json",
+        r#"{"timeout": 5000, "targets": [{"item": "localhost:4850", "retry": "fixed:50ms"}]}"#,
+    );
+
+    for path in [toml, yaml, json] {
+        let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+        cmd.arg("--config").arg(&path).assert().success();
+    }
+
+    drop(server);
+}
+
+#[test]
+fn config_command() {
+    let server = common::TestServer::new(4852, Duration::from_millis(10));
+
+    let path = config_path(
+        "command.toml",
+        r#"
+targets = ["localhost:4852"]
+command = ["sh", "-c", "exit 5"]
+"#,
+    );
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    cmd.arg("--config").arg(&path).assert().failure().code(5);
+
+    drop(server);
+}
+
+#[test]
+fn config_errors() {
+    let path = config_path(
+        "invalid.toml",
+        r#"timeout = "5s"
+targets = [
+    { item = "localhost:4853", stable = 0 },
+]
+"#,
+    );
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let output = cmd.arg("--config").arg(&path).assert().failure();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    assert!(stdout.contains("line 3"));
+    assert!(stdout.contains("stable '0' is not a positive number"));
+
+    let path = config_path("unknown.yaml", "timeout: 5s\ntargts: [localhost:4853]\n");
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let output = cmd.arg("--config").arg(&path).assert().failure();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    assert!(stdout.contains("unknown field `targts`"));
+    assert!(stdout.contains("line 2"));
+
+    let path = config_path(
+        "plain.toml",
+        "timeout = 30\ntargets = [\"localhost:4853\"]\n",
+    );
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let output = cmd.arg("--config").arg(&path).assert().failure();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    assert!(stdout.contains("'30' would be in milliseconds, use '30s' for seconds or '30ms'"));
+
+    let path = config_path(
+        "plain.yaml",
+        "targets:\n  - item: localhost:4853\n    attempt_timeout: 5\n",
+    );
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let output = cmd.arg("--config").arg(&path).assert().failure();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    assert!(stdout.contains("attempt_timeout '5' would be in milliseconds"));
+
+    let path = config_path(
+        "plain-item.toml",
+        "targets = [\"localhost:4853@timeout=5\"]\n",
+    );
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let output = cmd.arg("--config").arg(&path).assert().failure();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    assert!(stdout.contains("timeout '5' would be in milliseconds"));
+}
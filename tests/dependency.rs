@@ -0,0 +1,89 @@
+use assert_cmd::Command;
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+mod common;
+
+#[test]
+fn dependency_order() {
+    let ready = std::env::temp_dir().join("wait-for-them-dependency-ready");
+    let early = std::env::temp_dir().join("wait-for-them-dependency-early");
+    let _ = fs::remove_file(&ready);
+    let _ = fs::remove_file(&early);
+
+    // the dependent item would create the second file if it was checked too early
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("10s")
+        .arg(format!(
+            "exec:test -f {} || (touch {} && false)@after=setup",
+            ready.display(),
+            early.display()
+        ))
+        .arg(format!(
+            "exec:sleep 0.5 && touch {}@name=setup",
+            ready.display()
+        ));
+    cmd.assert().success();
+    assert!(!early.exists());
+}
+
+#[test]
+fn dependency_group() {
+    let server = common::TestServer::new(4840, Duration::from_millis(10));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("10s")
+        .arg("--group")
+        .arg("db=any:localhost:4841,localhost:4840")
+        .arg("localhost:4840@after=db");
+    cmd.assert().success();
+
+    drop(server);
+}
+
+#[test]
+fn dependency_failed() {
+    // dependent item fails as soon as its dependency fails
+    let instant = Instant::now();
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("30s")
+        .arg("localhost:4842@name=db,timeout=500ms")
+        .arg("localhost:4843@after=db");
+    cmd.assert().failure().code(2);
+    assert!(instant.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn dependency_cycle() {
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("localhost:4844@name=a,after=b")
+        .arg("localhost:4845@name=b,after=a");
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    assert!(String::from_utf8_lossy(&output).contains("dependency cycle a -> b -> a"));
+}
+
+#[test]
+fn dependency_cancelled_member() {
+    let server = common::TestServer::new(4846, Duration::from_millis(10));
+
+    // b is no longer waited for once a is ready, so its dependents follow the group
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("10s")
+        .arg("--group")
+        .arg("g=any:localhost:4846@name=a,localhost:4847@name=b")
+        .arg("localhost:4846@after=b");
+    cmd.assert().success();
+
+    drop(server);
+}
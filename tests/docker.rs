@@ -0,0 +1,111 @@
+#![cfg(all(feature = "docker", unix))]
+
+use assert_cmd::Command;
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixListener,
+    path::PathBuf,
+    thread,
+};
+
+/// Docker Engine API stand-in listening on a unix socket
+///
+/// * `web` - running and healthy
+/// * `plain` - running without healthcheck
+/// * `db` - running and unhealthy
+/// * `old` - exited
+fn docker_server(name: &str) -> PathBuf {
+    let socket = std::env::temp_dir().join(format!("wait-for-them-docker-{name}.sock"));
+    let _ = std::fs::remove_file(&socket);
+    let listener = UnixListener::bind(&socket).unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+            }
+
+            let container = request_line
+                .split_whitespace()
+                .nth(1)
+                .and_then(|e| e.strip_prefix("/containers/"))
+                .and_then(|e| e.strip_suffix("/json"))
+                .unwrap_or_default();
+            let (status, body) = match container {
+                "web" => (
+                    "200 OK",
+                    r#"{"State": {"Status": "running", "Health": {"Status": "healthy", "Log": []}}}"#,
+                ),
+                "plain" => ("200 OK", r#"{"State": {"Status": "running"}}"#),
+                "db" => (
+                    "200 OK",
+                    r#"{"State": {"Status": "running", "Health": {"Status": "unhealthy", "Log": [
+                        {"ExitCode": 1, "Output": "first failure\n"},
+                        {"ExitCode": 1, "Output": "pg_isready: no response\n"}
+                    ]}}}"#,
+                ),
+                "old" => ("200 OK", r#"{"State": {"Status": "exited"}}"#),
+                _ => ("404 Not Found", r#"{"message": "No such container"}"#),
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = reader.get_mut().write_all(response.as_bytes());
+        }
+    });
+    socket
+}
+
+#[test]
+fn docker_healthy() {
+    let socket = docker_server("healthy");
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .env("DOCKER_HOST", format!("unix://{}", socket.display()))
+        .arg("--timeout")
+        .arg("5000")
+        .arg("docker://web")
+        .arg("docker://plain");
+    cmd.assert().success();
+}
+
+#[test]
+fn docker_unhealthy() {
+    let socket = docker_server("unhealthy");
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .env("DOCKER_HOST", format!("unix://{}", socket.display()))
+        .arg("--timeout")
+        .arg("1000")
+        .arg("docker://db")
+        .arg("docker://old")
+        .arg("docker://missing");
+    cmd.assert().failure().code(3);
+}
+
+#[test]
+fn docker_health_log() {
+    let socket = docker_server("health-log");
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .env("DOCKER_HOST", format!("unix://{}", socket.display()))
+        .arg("--output")
+        .arg("json")
+        .arg("--timeout")
+        .arg("500ms")
+        .arg("docker://db");
+    let output = cmd.assert().failure().code(1).get_output().stdout.clone();
+    let json = String::from_utf8_lossy(&output);
+    // the last healthcheck output is the reason
+    assert!(json.contains("pg_isready: no response"));
+    assert!(!json.contains("first failure"));
+}
@@ -0,0 +1,49 @@
+use assert_cmd::Command;
+use std::time::Duration;
+
+#[test]
+fn exec_ok() {
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg("exec:true")
+        .arg("exec:test 1 -eq 1 && echo ready");
+    cmd.assert().success();
+}
+
+#[test]
+fn exec_retry() {
+    let marker = std::env::temp_dir().join("wait-for-them-exec-retry");
+    let _ = std::fs::remove_file(&marker);
+
+    // the file is created during the second attempt
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd.arg("--timeout").arg("5000").arg(format!(
+        "exec:test -f {0} || (touch {0} && false)",
+        marker.display()
+    ));
+    cmd.assert().success();
+}
+
+#[test]
+fn exec_fail() {
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("1000")
+        .arg("exec:false")
+        .arg("exec:echo not ready >&2; exit 2");
+    cmd.assert().failure().code(2);
+}
+
+#[test]
+fn exec_attempt_killed() {
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .timeout(Duration::from_secs(10))
+        .arg("--timeout")
+        .arg("1000")
+        .arg("exec:sleep 60");
+    cmd.assert().failure().code(1);
+}
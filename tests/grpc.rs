@@ -0,0 +1,81 @@
+#![cfg(feature = "grpc")]
+
+use assert_cmd::Command;
+use bytes::Bytes;
+use std::thread;
+
+/// Serves `grpc.health.v1.Health/Check` over h2c
+///
+/// `SERVING` is returned for `ready` service (and the server itself),
+/// `NOT_SERVING` for any other service.
+fn grpc_server(port: u16) {
+    thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async move {
+            let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{port}"))
+                .await
+                .expect("can't connect");
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut connection = h2::server::handshake(socket).await.unwrap();
+                    while let Some(Ok((request, mut respond))) = connection.accept().await {
+                        assert_eq!(request.uri().path(), "/grpc.health.v1.Health/Check");
+                        let mut body = request.into_body();
+                        let mut data = vec![];
+                        while let Some(Ok(chunk)) = body.data().await {
+                            data.extend_from_slice(&chunk);
+                        }
+                        // frame header (5 bytes) + field header (2 bytes)
+                        let service = data.get(7..).unwrap_or_default();
+                        let status = if service.is_empty() || service == b"ready" {
+                            1
+                        } else {
+                            2
+                        };
+
+                        let response = http::Response::builder()
+                            .status(200)
+                            .header("content-type", "application/grpc")
+                            .body(())
+                            .unwrap();
+                        let mut send = respond.send_response(response, false).unwrap();
+                        send.send_data(Bytes::from(vec![0, 0, 0, 0, 2, 0x08, status]), false)
+                            .unwrap();
+                        let mut trailers = http::HeaderMap::new();
+                        trailers.insert("grpc-status", "0".parse().unwrap());
+                        send.send_trailers(trailers).unwrap();
+                    }
+                });
+            }
+        });
+    });
+}
+
+#[test]
+fn grpc_serving() {
+    grpc_server(4300);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg("grpc://localhost:4300")
+        .arg("grpc://127.0.0.1:4300/ready");
+    cmd.assert().success();
+}
+
+#[test]
+fn grpc_not_serving() {
+    grpc_server(4301);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("1000")
+        .arg("grpc://localhost:4301/down")
+        .arg("grpc://localhost:4302");
+    cmd.assert().failure().code(2);
+}
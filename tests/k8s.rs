@@ -0,0 +1,131 @@
+#![cfg(feature = "k8s")]
+
+use assert_cmd::Command;
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+};
+
+/// Kubernetes API server stand-in
+///
+/// * deployment `web` - all replicas ready
+/// * deployment `api` - one of three replicas ready
+/// * statefulset `db` - ready
+/// * pod `web-0` - ready, pod `web-1` - pending
+/// * job `migrate` - complete, job `seed` - failed
+fn api_server(port: u16) {
+    let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+            }
+
+            let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+            let (status, body) = match path {
+                "/apis/apps/v1/namespaces/default/deployments/web" => (
+                    "200 OK",
+                    r#"{"metadata": {"generation": 2}, "spec": {"replicas": 2},
+                        "status": {"observedGeneration": 2, "readyReplicas": 2}}"#,
+                ),
+                "/apis/apps/v1/namespaces/default/deployments/api" => (
+                    "200 OK",
+                    r#"{"metadata": {"generation": 1}, "spec": {"replicas": 3},
+                        "status": {"observedGeneration": 1, "readyReplicas": 1}}"#,
+                ),
+                "/apis/apps/v1/namespaces/default/statefulsets/db" => (
+                    "200 OK",
+                    r#"{"metadata": {"generation": 1}, "spec": {"replicas": 1},
+                        "status": {"observedGeneration": 1, "readyReplicas": 1}}"#,
+                ),
+                "/api/v1/namespaces/default/pods/web-0" => (
+                    "200 OK",
+                    r#"{"status": {"phase": "Running", "conditions": [
+                        {"type": "Ready", "status": "True"}]}}"#,
+                ),
+                "/api/v1/namespaces/default/pods/web-1" => (
+                    "200 OK",
+                    r#"{"status": {"phase": "Pending", "conditions": [
+                        {"type": "Ready", "status": "False"}]}}"#,
+                ),
+                "/apis/batch/v1/namespaces/default/jobs/migrate" => (
+                    "200 OK",
+                    r#"{"spec": {"completions": 1}, "status": {"succeeded": 1, "conditions": [
+                        {"type": "Complete", "status": "True"}]}}"#,
+                ),
+                "/apis/batch/v1/namespaces/default/jobs/seed" => (
+                    "200 OK",
+                    r#"{"spec": {"completions": 1}, "status": {"failed": 6, "conditions": [
+                        {"type": "Failed", "status": "True", "reason": "BackoffLimitExceeded"}]}}"#,
+                ),
+                _ => ("404 Not Found", r#"{"kind": "Status", "code": 404}"#),
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = reader.get_mut().write_all(response.as_bytes());
+        }
+    });
+}
+
+#[test]
+fn k8s_ready() {
+    api_server(4600);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .env("WAIT_FOR_THEM_K8S_API", "http://127.0.0.1:4600")
+        .arg("--timeout")
+        .arg("5000")
+        .arg("k8s://default/deployment/web")
+        .arg("k8s://default/sts/db")
+        .arg("k8s://default/pod/web-0")
+        .arg("k8s://default/job/migrate");
+    cmd.assert().success();
+}
+
+#[test]
+fn k8s_not_ready() {
+    api_server(4601);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .env("WAIT_FOR_THEM_K8S_API", "http://127.0.0.1:4601")
+        .arg("--timeout")
+        .arg("1000")
+        .arg("k8s://default/deployment/api")
+        .arg("k8s://default/pod/web-1")
+        .arg("k8s://default/job/seed")
+        .arg("k8s://default/deployment/missing");
+    cmd.assert().failure().code(4);
+}
+
+#[test]
+fn k8s_token_over_http() {
+    api_server(4602);
+    let token = std::env::temp_dir().join("wait-for-them-k8s-token");
+    std::fs::write(&token, "secret\n").unwrap();
+
+    // the token is not sent to a plain http url
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .env("WAIT_FOR_THEM_K8S_API", "http://127.0.0.1:4602")
+        .env("WAIT_FOR_THEM_K8S_TOKEN_FILE", &token)
+        .arg("--output")
+        .arg("json")
+        .arg("--timeout")
+        .arg("500ms")
+        .arg("k8s://default/deployment/web");
+    let output = cmd.assert().failure().code(1).get_output().stdout.clone();
+    assert!(String::from_utf8_lossy(&output).contains(
+        "WAIT_FOR_THEM_K8S_TOKEN_FILE requires WAIT_FOR_THEM_K8S_API to be an https:// url"
+    ));
+}
@@ -0,0 +1,134 @@
+use assert_cmd::Command;
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+fn log_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("wait-for-them-log-{name}.log"));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn append(path: &Path, content: &str) {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .unwrap();
+    file.write_all(content.as_bytes()).unwrap();
+}
+
+/// Runs given function in a thread after a short delay
+fn later(f: impl FnOnce() + Send + 'static) {
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(500));
+        f();
+    });
+}
+
+fn wait_for(path: &Path, options: &str, timeout: u64) -> Command {
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    cmd.arg("--timeout")
+        .arg(timeout.to_string())
+        .arg(format!("log://{}?match={options}", path.display()));
+    cmd
+}
+
+#[test]
+fn log_existing_lines() {
+    let path = log_path("existing");
+    append(&path, "booting\nServer started on port 8080\n");
+
+    wait_for(&path, "started%20on%20port", 1000)
+        .assert()
+        .failure()
+        .code(1);
+    wait_for(&path, "started%20on%20port&from=start", 5000)
+        .assert()
+        .success();
+}
+
+#[test]
+fn log_appended() {
+    let path = log_path("appended");
+    append(&path, "booting\n");
+
+    let appended = path.clone();
+    later(move || {
+        append(&appended, "loading ");
+        append(&appended, "config\nServer started on port 8080\n");
+    });
+    wait_for(&path, "^Server%20started", 5000)
+        .assert()
+        .success();
+}
+
+#[test]
+fn log_created() {
+    let path = log_path("created");
+
+    let created = path.clone();
+    later(move || append(&created, "Server started on port 8080\n"));
+    wait_for(&path, "started", 5000).assert().success();
+}
+
+#[test]
+fn log_rotated() {
+    let path = log_path("rotated");
+    append(&path, "booting\n");
+
+    let rotated = path.clone();
+    later(move || {
+        fs::rename(&rotated, rotated.with_extension("log.1")).unwrap();
+        append(&rotated, "ready\n");
+    });
+    wait_for(&path, "ready", 5000).assert().success();
+}
+
+#[test]
+fn log_truncated() {
+    let path = log_path("truncated");
+    append(&path, &"booting\n".repeat(100));
+
+    let truncated = path.clone();
+    later(move || fs::write(&truncated, "ready\n").unwrap());
+    wait_for(&path, "ready", 5000).assert().success();
+}
+
+#[test]
+fn log_crlf() {
+    let path = log_path("crlf");
+
+    let appended = path.clone();
+    later(move || append(&appended, "booting\r\nServer started\r\n"));
+    wait_for(&path, "started$", 5000).assert().success();
+}
+
+#[test]
+fn log_truncated_and_grown() {
+    let path = log_path("copytruncate");
+    append(&path, &"booting\n".repeat(10));
+
+    // copytruncate followed by more output than the old file had
+    let truncated = path.clone();
+    later(move || fs::write(&truncated, format!("ready\n{}\n", "x".repeat(200))).unwrap());
+    wait_for(&path, "ready", 3000).assert().success();
+}
+
+#[test]
+fn log_long_line() {
+    let path = log_path("long");
+
+    let appended = path.clone();
+    later(move || {
+        append(
+            &appended,
+            &format!("{}ready\n", "x".repeat(2 * 1024 * 1024)),
+        )
+    });
+    wait_for(&path, "ready", 5000).assert().success();
+}
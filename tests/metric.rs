@@ -0,0 +1,76 @@
+#![cfg(feature = "http")]
+
+use assert_cmd::Command;
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+};
+
+const EXPOSITION: &str = r#"# HELP kafka_consumer_lag Lag of the consumer
+# TYPE kafka_consumer_lag gauge
+kafka_consumer_lag{topic="orders",partition="0"} 12
+kafka_consumer_lag{topic="orders",partition="1"} 250 1700000000000
+kafka_consumer_lag{topic="payments",partition="0"} 3
+# TYPE app_cache_warm gauge
+app_cache_warm 1
+app_escaped{path="C:\\tmp\"x\""} +Inf
+"#;
+
+/// Serves prometheus exposition on `/metrics`
+fn metrics_server(port: u16) {
+    let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+            }
+
+            let (status, body) = match request_line.split_whitespace().nth(1) {
+                Some("/metrics") => ("200 OK", EXPOSITION),
+                _ => ("404 Not Found", ""),
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = reader.get_mut().write_all(response.as_bytes());
+        }
+    });
+}
+
+#[test]
+fn metric_satisfied() {
+    metrics_server(4700);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg("metric+http://127.0.0.1:4700/metrics?name=app_cache_warm&value=1")
+        .arg("metric+http://127.0.0.1:4700/metrics?name=kafka_consumer_lag{topic=payments}&op=lt&value=100")
+        .arg("metric+http://127.0.0.1:4700/metrics?name=kafka_consumer_lag{partition=\"0\"}&op=le&value=12")
+        .arg("metric+http://127.0.0.1:4700/metrics?name=app_escaped{path=\"C:\\\\tmp\\\"x\\\"\"}&op=gt&value=0");
+    cmd.assert().success();
+}
+
+#[test]
+fn metric_unsatisfied() {
+    metrics_server(4701);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("1000")
+        .arg("metric+http://127.0.0.1:4701/metrics?name=app_cache_warm&op=gt&value=1")
+        .arg("metric+http://127.0.0.1:4701/metrics?name=kafka_consumer_lag{topic=\"orders\"}&op=lt&value=100")
+        .arg("metric+http://127.0.0.1:4701/metrics?name=missing&value=1")
+        .arg("metric+http://127.0.0.1:4701/other?name=app_cache_warm&value=1");
+    cmd.assert().failure().code(4);
+}
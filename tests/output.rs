@@ -0,0 +1,70 @@
+#![cfg(feature = "json")]
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn json() {
+    let server = common::TestServer::new(4880, Duration::from_millis(10));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("-t")
+        .arg("500ms")
+        .arg("localhost:4880")
+        .arg("localhost:4881");
+    let output = cmd.assert().failure().code(1).get_output().stdout.clone();
+    let results: Value = serde_json::from_slice(&output).unwrap();
+
+    let ready = &results[0];
+    assert_eq!(ready["target"], "localhost:4880");
+    assert_eq!(ready["status"], "success");
+    assert!(ready["attempts"].as_u64().unwrap() >= 1);
+    assert!(ready["elapsed_ms"].is_u64());
+    assert!(ready["started_at"].as_str().unwrap().ends_with('Z'));
+    assert!(ready["finished_at"].is_string());
+
+    let failed = &results[1];
+    assert_eq!(failed["target"], "localhost:4881");
+    assert_eq!(failed["status"], "failure");
+    assert!(failed["attempts"].as_u64().unwrap() > 1);
+    assert!(failed["last_error"].is_string());
+
+    drop(server);
+}
+
+#[test]
+fn ndjson() {
+    let server = common::TestServer::new(4882, Duration::from_millis(10));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("-o")
+        .arg("ndjson")
+        .arg("--any")
+        .arg("localhost:4882")
+        .arg("localhost:4883")
+        .arg("--")
+        .arg("true");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    // no text is mixed with the events
+    let events: Vec<Value> = String::from_utf8_lossy(&output)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let names: Vec<&str> = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["start", "start", "success", "cancelled"]);
+    assert_eq!(events[2]["target"], "localhost:4882");
+    assert_eq!(events[3]["target"], "localhost:4883");
+    assert!(events[3]["finished_at"].is_null());
+
+    drop(server);
+}
@@ -0,0 +1,64 @@
+#![cfg(target_os = "linux")]
+
+use assert_cmd::Command;
+use std::{process, thread, time::Duration};
+
+#[test]
+fn pid_file() {
+    let pid_file = std::env::temp_dir().join("wait-for-them-pid-file.pid");
+    let _ = std::fs::remove_file(&pid_file);
+
+    let path = pid_file.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(300));
+        std::fs::write(path, format!("{}\n", process::id())).unwrap();
+    });
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg(format!("pid:{}", pid_file.display()))
+        .arg(format!("pid:{}", process::id()));
+    cmd.assert().success();
+}
+
+#[test]
+fn pid_exited() {
+    // the child stays a zombie as it is not reaped
+    let mut child = process::Command::new("sleep").arg("0.3").spawn().unwrap();
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg(format!("pid:{}?exited", child.id()))
+        .arg("pid:/nonexistent/wait-for-them.pid?exited");
+    cmd.assert().success();
+
+    child.wait().unwrap();
+}
+
+#[test]
+fn process_name() {
+    let mut child = process::Command::new("sleep").arg("5").spawn().unwrap();
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg("process:sleep")
+        .arg("process:wait-for-them-nonexistent?exited");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("1000")
+        .arg("process:wait-for-them-nonexistent")
+        .arg(format!("pid:{}?exited", child.id()));
+    cmd.assert().failure().code(2);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
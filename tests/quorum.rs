@@ -0,0 +1,70 @@
+use assert_cmd::Command;
+use std::time::{Duration, Instant};
+
+mod common;
+
+#[test]
+fn any() {
+    let server = common::TestServer::new(4830, Duration::from_millis(10));
+
+    // closed port is no longer waited for once the open one is verified
+    let instant = Instant::now();
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("30s")
+        .arg("--any")
+        .arg("localhost:4831")
+        .arg("localhost:4830");
+    cmd.assert().success();
+    assert!(instant.elapsed() < Duration::from_secs(10));
+
+    drop(server);
+}
+
+#[test]
+fn at_least() {
+    let server = common::TestServer::new(4832, Duration::from_millis(10));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("500ms")
+        .arg("--at-least")
+        .arg("2")
+        .arg("localhost:4832")
+        .arg("localhost:4833")
+        .arg("localhost:4834");
+    cmd.assert().failure().code(1);
+
+    drop(server);
+}
+
+#[test]
+fn group() {
+    let server = common::TestServer::new(4835, Duration::from_millis(10));
+
+    let instant = Instant::now();
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("30s")
+        .arg("--group")
+        .arg("cache=any:localhost:4836,localhost:4835")
+        .arg("localhost:4835");
+    cmd.assert().success();
+    assert!(instant.elapsed() < Duration::from_secs(10));
+
+    // both groups count their missing items
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("500ms")
+        .arg("--group")
+        .arg("cache=any:localhost:4836,localhost:4837")
+        .arg("--group")
+        .arg("db=2:localhost:4835,localhost:4838@timeout=300ms");
+    cmd.assert().failure().code(2);
+
+    drop(server);
+}
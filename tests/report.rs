@@ -0,0 +1,106 @@
+use assert_cmd::Command;
+use std::{fs, time::Duration};
+
+mod common;
+
+#[test]
+fn junit() {
+    let server = common::TestServer::new(4890, Duration::from_millis(10));
+    let path = std::env::temp_dir().join("wait-for-them-report.xml");
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--report")
+        .arg(format!("junit={}", path.display()))
+        .arg("-t")
+        .arg("500ms")
+        .arg("localhost:4890")
+        .arg("localhost:4891");
+    cmd.assert().failure().code(1);
+
+    let xml = fs::read_to_string(&path).unwrap();
+    assert!(xml.contains(r#"<testsuites name="wait-for-them" tests="2" failures="1""#));
+    assert!(xml.contains(r#"<testcase name="localhost:4890" classname="wait-for-them" time=""#));
+    assert!(xml.contains(r#"<testcase name="localhost:4891""#));
+    assert!(xml.contains(r#"<failure message="connection refused"#));
+
+    drop(server);
+}
+
+#[test]
+fn tap() {
+    let server = common::TestServer::new(4892, Duration::from_millis(10));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--report")
+        .arg("tap")
+        .arg("-t")
+        .arg("500ms")
+        .arg("--group")
+        .arg("servers=any:localhost:4893,localhost:4892")
+        .arg("localhost:4893@required=false");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let tap = String::from_utf8_lossy(&output);
+    // the progress is not mixed with the report
+    assert!(tap.starts_with("TAP version 13\n1..3\n"));
+    assert!(tap.contains("not ok 1 - localhost:4893\n"));
+    assert!(tap.contains("  message: \"connection refused"));
+    assert!(tap.contains("ok 2 - servers: localhost:4893 # SKIP no longer waited for\n"));
+    assert!(tap.contains("ok 3 - servers: localhost:4892\n"));
+    assert!(!tap.contains("verified"));
+
+    drop(server);
+}
+
+#[test]
+fn tap_duration() {
+    let server = common::TestServer::new(4894, Duration::from_millis(300));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--report")
+        .arg("tap")
+        .arg("-t")
+        .arg("2s")
+        .arg("localhost:4894");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let tap = String::from_utf8_lossy(&output);
+    let duration: u64 = tap
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("duration_ms: "))
+        .unwrap()
+        .parse()
+        .unwrap();
+    // measured since the start, not since the last attempt
+    assert!(duration >= 300);
+
+    drop(server);
+}
+
+#[test]
+fn tap_only() {
+    let server = common::TestServer::new(4895, Duration::from_millis(100));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--report")
+        .arg("tap")
+        .arg("-t")
+        .arg("1s")
+        .arg("localhost:4895@stable=2")
+        .arg("localhost:4896@required=false");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let tap = String::from_utf8_lossy(&output);
+    let mut lines = tap.lines();
+    assert_eq!(lines.next(), Some("TAP version 13"));
+    assert_eq!(lines.next(), Some("1..2"));
+    for line in lines {
+        assert!(
+            line.starts_with("ok ") || line.starts_with("not ok ") || line.starts_with("  "),
+            "'{line}' is not a part of TAP"
+        );
+    }
+
+    drop(server);
+}
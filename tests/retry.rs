@@ -0,0 +1,53 @@
+use assert_cmd::Command;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn retry_exponential() {
+    let server = common::TestServer::new(4810, Duration::from_millis(500));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg("--retry")
+        .arg("exponential:50:400")
+        .arg("localhost:4810");
+    cmd.assert().success();
+
+    drop(server);
+}
+
+#[test]
+fn retry_jitter() {
+    let server = common::TestServer::new(4811, Duration::from_millis(500));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg("--retry")
+        .arg("jitter:50:400")
+        .arg("localhost:4811");
+    cmd.assert().success();
+
+    drop(server);
+}
+
+#[test]
+fn retry_slow() {
+    // the port is opened while waiting for the second attempt
+    let server = common::TestServer::new(4812, Duration::from_millis(200));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("1500")
+        .arg("--retry")
+        .arg("fixed:3000")
+        .arg("localhost:4812");
+    cmd.assert().failure().code(1);
+
+    drop(server);
+}
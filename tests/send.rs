@@ -0,0 +1,40 @@
+use std::time::Duration;
+use wait_for_them::{wait_for_them, Output, ToCheck, Waiter};
+
+mod common;
+
+/// Fails to compile when the waiting is not `Send`
+fn spawn<F>(future: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: std::future::Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(future)
+}
+
+#[test]
+fn multi_thread_runtime() {
+    let server = common::TestServer::new(4910, Duration::from_millis(10));
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        let targets = vec![ToCheck::HostnameAndPort("localhost".into(), 4910)];
+        let res = spawn(async move { wait_for_them(&targets, Some(5000), None, false).await });
+        assert!(res.await.unwrap()[0].is_some());
+
+        let waiter = Waiter::builder()
+            .target("localhost:4910@stable=2".parse().unwrap())
+            .timeout(Duration::from_secs(5))
+            .output(Output::Silent)
+            .cancel_on(tokio::time::sleep(Duration::from_secs(10)))
+            .build()
+            .unwrap();
+        let res = spawn(waiter.wait()).await.unwrap();
+        assert!(res[0][0].is_ready());
+    });
+
+    drop(server);
+}
@@ -0,0 +1,46 @@
+use assert_cmd::Command;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn environment() {
+    let server = common::TestServer::new(4860, Duration::from_millis(10));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .env("WAIT_FOR_THEM_TARGETS", "localhost:4860\nlocalhost:4861")
+        .env("WAIT_FOR_THEM_TIMEOUT", "500ms");
+    cmd.assert().failure().code(1);
+
+    // command line timeout takes precedence
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .env("WAIT_FOR_THEM_TARGETS", "localhost:4860")
+        .env("WAIT_FOR_THEM_TIMEOUT", "1ms")
+        .arg("-t")
+        .arg("5s");
+    cmd.assert().success();
+
+    drop(server);
+}
+
+#[test]
+fn stdin() {
+    let server = common::TestServer::new(4862, Duration::from_millis(10));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("-t")
+        .arg("5s")
+        .arg("-")
+        .write_stdin("# generated\nlocalhost:4862\n\nlocalhost:4862@stable=2\n");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd.arg("-").write_stdin("localhost:4862\nlocalhost:bad\n");
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    assert!(String::from_utf8_lossy(&output).starts_with("stdin line 2: "));
+
+    drop(server);
+}
@@ -0,0 +1,44 @@
+use assert_cmd::Command;
+use std::{
+    net::TcpListener,
+    thread,
+    time::{Duration, Instant},
+};
+
+mod common;
+
+#[test]
+fn stable() {
+    let server = common::TestServer::new(4800, Duration::from_millis(10));
+
+    let instant = Instant::now();
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg("localhost:4800@stable=3,stable_for=500")
+        .arg("http://localhost:4800/");
+    cmd.assert().success();
+    assert!(instant.elapsed() >= Duration::from_millis(500));
+
+    drop(server);
+}
+
+#[test]
+fn stable_flapping() {
+    // the port is opened only for a short while
+    let listener = TcpListener::bind("127.0.0.1:4801").unwrap();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(300));
+        drop(listener);
+    });
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("2000")
+        .arg("--stable-for")
+        .arg("1000")
+        .arg("localhost:4801");
+    cmd.assert().failure().code(1);
+}
@@ -0,0 +1,41 @@
+use assert_cmd::Command;
+use std::time::{Duration, Instant};
+
+mod common;
+
+#[test]
+fn target_timeout() {
+    let instant = Instant::now();
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("30000")
+        .arg("localhost:4820@timeout=500ms");
+    cmd.assert().failure().code(1);
+    assert!(instant.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn target_optional() {
+    let server = common::TestServer::new(4821, Duration::from_millis(10));
+
+    // optional item is not waited for once the required ones are ready
+    let instant = Instant::now();
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("localhost:4821")
+        .arg("localhost:4822@required=false");
+    cmd.assert().success();
+    assert!(instant.elapsed() < Duration::from_secs(10));
+
+    // failed optional item is not counted
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("500")
+        .arg("localhost:4822@required=false")
+        .arg("localhost:4821@timeout=5s");
+    cmd.assert().success();
+
+    drop(server);
+}
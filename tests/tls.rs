@@ -0,0 +1,203 @@
+#![cfg(feature = "tls")]
+
+use assert_cmd::Command;
+use native_tls::{Identity, TlsAcceptor};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    thread,
+};
+
+/// Serves TLS handshakes with a self-signed certificate for `localhost`
+/// and returns a path to the certificate
+fn tls_server(port: u16) -> PathBuf {
+    tls_server_with(port, |_| true)
+}
+
+/// Same as `tls_server`, but `preamble` is called before the handshake
+/// and the handshake is skipped when it returns `false`
+fn tls_server_with(port: u16, preamble: fn(&mut TcpStream) -> bool) -> PathBuf {
+    let rcgen::CertifiedKey { cert, signing_key } =
+        rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let path = std::env::temp_dir().join(format!("wait-for-them-tls-{port}.pem"));
+    std::fs::write(&path, cert.pem()).unwrap();
+
+    let identity = Identity::from_pkcs8(
+        cert.pem().as_bytes(),
+        signing_key.serialize_pem().as_bytes(),
+    )
+    .unwrap();
+    let acceptor = TlsAcceptor::new(identity).unwrap();
+    let listener = TcpListener::bind(format!("127.0.0.1:{port}")).expect("can't connect");
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            if !preamble(&mut stream) {
+                continue;
+            }
+            if let Ok(mut stream) = acceptor.accept(stream) {
+                let _ = stream.write_all(b"OK");
+            }
+        }
+    });
+    path
+}
+
+#[test]
+fn tls_ok() {
+    let ca = tls_server(4200);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd.arg("--timeout").arg("5000").arg(format!(
+        "tls://localhost:4200?ca={}&name=localhost&days=1",
+        ca.display()
+    ));
+    cmd.assert().success();
+}
+
+#[test]
+fn tls_untrusted() {
+    tls_server(4201);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd.arg("--timeout").arg("1000").arg("tls://localhost:4201");
+    cmd.assert().failure().code(1);
+}
+
+#[test]
+fn tls_insecure() {
+    tls_server(4202);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg("tls://127.0.0.1:4202?insecure");
+    cmd.assert().success();
+}
+
+#[test]
+fn tls_certificate_mismatch() {
+    let ca = tls_server(4203);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("1000")
+        .arg(format!(
+            "tls://localhost:4203?ca={}&name=www.example.com",
+            ca.display()
+        ))
+        .arg(format!(
+            "tls://localhost:4203?ca={}&days=1000000",
+            ca.display()
+        ))
+        .arg(format!(
+            "tls://localhost:4203?insecure&fingerprint={}",
+            "00".repeat(32)
+        ));
+    cmd.assert().failure().code(3);
+}
+
+fn read_line(stream: &mut TcpStream) -> String {
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).unwrap();
+    line
+}
+
+#[test]
+fn starttls_smtp() {
+    let ca = tls_server_with(4204, |stream| {
+        stream.write_all(b"220 mail.localhost ESMTP\r\n").unwrap();
+        assert!(read_line(stream).starts_with("EHLO"));
+        stream
+            .write_all(b"250-mail.localhost\r\n250-PIPELINING\r\n250 STARTTLS\r\n")
+            .unwrap();
+        assert_eq!(read_line(stream), "STARTTLS\r\n");
+        stream.write_all(b"220 Ready to start TLS\r\n").unwrap();
+        true
+    });
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd.arg("--timeout").arg("5000").arg(format!(
+        "tls://localhost:4204?starttls=smtp&ca={}",
+        ca.display()
+    ));
+    cmd.assert().success();
+}
+
+#[test]
+fn starttls_ldap() {
+    let ca = tls_server_with(4205, |stream| {
+        let mut request = [0; 31];
+        stream.read_exact(&mut request).unwrap();
+        assert!(request.ends_with(b"1.3.6.1.4.1.1466.20037"));
+        stream
+            .write_all(b"\x30\x0c\x02\x01\x01\x78\x07\x0a\x01\x00\x04\x00\x04\x00")
+            .unwrap();
+        true
+    });
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd.arg("--timeout").arg("5000").arg(format!(
+        "tls://localhost:4205?starttls=ldap&ca={}",
+        ca.display()
+    ));
+    cmd.assert().success();
+}
+
+#[test]
+fn starttls_postgres() {
+    let ca = tls_server_with(4206, |stream| {
+        let mut request = [0; 8];
+        stream.read_exact(&mut request).unwrap();
+        assert_eq!(request, [0, 0, 0, 8, 4, 210, 22, 47]);
+        stream.write_all(b"S").unwrap();
+        true
+    });
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd.arg("--timeout").arg("5000").arg(format!(
+        "tls://localhost:4206?starttls=postgres&ca={}",
+        ca.display()
+    ));
+    cmd.assert().success();
+}
+
+#[test]
+fn starttls_refused() {
+    let ca = tls_server_with(4207, |stream| {
+        let mut request = [0; 8];
+        let _ = stream.read_exact(&mut request);
+        let _ = stream.write_all(b"N");
+        false
+    });
+    tls_server_with(4208, |stream| {
+        let _ = stream.write_all(b"* OK IMAP4rev1 ready\r\n");
+        read_line(stream);
+        let _ = stream.write_all(b"a001 NO not now\r\n");
+        false
+    });
+    tls_server_with(4209, |stream| {
+        let _ = stream.write_all(b"+OK POP3 ready\r\n");
+        read_line(stream);
+        let _ = stream.write_all(b"+OK Begin TLS negotiation\r\n");
+        true
+    });
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("1000")
+        .arg(format!(
+            "tls://localhost:4207?starttls=postgres&ca={}",
+            ca.display()
+        ))
+        .arg(format!(
+            "tls://localhost:4208?starttls=imap&ca={}",
+            ca.display()
+        ))
+        // certificate is not trusted
+        .arg("tls://localhost:4209?starttls=pop3");
+    cmd.assert().failure().code(3);
+}
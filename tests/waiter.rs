@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+use wait_for_them::{wait_for_them, Output, Quorum, RetryPolicy, Status, ToCheck, Waiter};
+
+mod common;
+
+#[tokio::test]
+async fn builder() {
+    let server = common::TestServer::new(4900, Duration::from_millis(10));
+
+    let waiter = Waiter::builder()
+        .target("localhost:4900".parse().unwrap())
+        .target("localhost:4901@required=false".parse().unwrap())
+        .quorum(Quorum::Any)
+        .timeout(Duration::from_millis(500))
+        .retry(RetryPolicy::Fixed(Duration::from_millis(50)))
+        .output(Output::Silent)
+        .build()
+        .unwrap();
+    let res = waiter.wait().await;
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0][0].status, Status::Ready);
+
+    assert!(Waiter::builder()
+        .target("localhost:4900@after=unknown".parse().unwrap())
+        .build()
+        .is_err());
+    assert!(Waiter::builder()
+        .quorum(Quorum::AtLeast(2))
+        .target("localhost:4900".parse().unwrap())
+        .build()
+        .is_err());
+
+    drop(server);
+}
+
+#[tokio::test]
+async fn cancel() {
+    let instant = Instant::now();
+    let waiter = Waiter::builder()
+        .target("localhost:4902".parse().unwrap())
+        .output(Output::Silent)
+        .cancel_on(tokio::time::sleep(Duration::from_millis(300)))
+        .build()
+        .unwrap();
+    let res = waiter.wait().await;
+    assert_eq!(res[0][0].status, Status::Cancelled);
+    assert!(instant.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn compatibility() {
+    let server = common::TestServer::new(4903, Duration::from_millis(10));
+
+    let res = wait_for_them(
+        &[
+            ToCheck::HostnameAndPort("localhost".into(), 4903),
+            ToCheck::HostnameAndPort("localhost".into(), 4904),
+        ],
+        Some(500),
+        None,
+        true,
+    )
+    .await;
+    assert!(res[0].is_some());
+    assert!(res[1].is_none());
+
+    drop(server);
+}
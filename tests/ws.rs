@@ -0,0 +1,71 @@
+#![cfg(feature = "ws")]
+
+use assert_cmd::Command;
+use futures::{SinkExt, StreamExt};
+use std::{thread, time::Duration};
+
+mod common;
+
+/// WebSocket server which replies to `ping` with `pong`
+fn ws_server(port: u16) {
+    thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async move {
+            let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{port}"))
+                .await
+                .expect("can't connect");
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let Ok(mut stream) = tokio_tungstenite::accept_async(socket).await else {
+                        return;
+                    };
+                    while let Some(Ok(message)) = stream.next().await {
+                        if message.is_text() && message.to_text().unwrap() == "ping" {
+                            let _ = stream.send("pong".into()).await;
+                        }
+                    }
+                });
+            }
+        });
+    });
+}
+
+#[test]
+fn ws_upgrade() {
+    ws_server(4400);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("5000")
+        .arg("ws://localhost:4400/")
+        .arg("ws://127.0.0.1:4400/socket?token=x#send=ping&expect=^po+ng$");
+    cmd.assert().success();
+}
+
+#[test]
+fn ws_no_reply() {
+    ws_server(4401);
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd
+        .arg("--timeout")
+        .arg("1500")
+        .arg("ws://localhost:4401/#send=hello&expect=pong");
+    cmd.assert().failure().code(1);
+}
+
+#[test]
+fn ws_not_upgraded() {
+    // plain http server returns 200 instead of 101
+    let server = common::TestServer::new(4402, Duration::from_millis(10));
+
+    let mut cmd = Command::cargo_bin("wait-for-them").unwrap();
+    let cmd = cmd.arg("--timeout").arg("1000").arg("ws://localhost:4402/");
+    cmd.assert().failure().code(1);
+
+    drop(server);
+}